@@ -68,7 +68,10 @@ impl LimelightController for Snowflake {
     fn handle_mousemove(&mut self, x: f32, y: f32) -> ShouldRequestAnimationFrame {
         let rot = nalgebra::Rotation3::from_euler_angles(-y / 2., x / 2., 0.);
 
-        self.snowflake.transform().set_value(rot.to_homogeneous().into());
+        self.snowflake
+            .transform()
+            .expect("The layer has its own transform.")
+            .set_value(rot.to_homogeneous().into());
 
         true
     }
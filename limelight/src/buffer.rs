@@ -1,4 +1,9 @@
-use crate::{shadow_gpu::BufferHandle, webgl::buffer::BufferUsageHint, Attribute};
+use crate::{
+    error::{Error, Result},
+    shadow_gpu::{BufferHandle, PendingRead},
+    webgl::buffer::BufferUsageHint,
+    Attribute, AttributeBinding, Renderer,
+};
 use std::marker::PhantomData;
 
 #[allow(clippy::len_without_is_empty)]
@@ -32,6 +37,75 @@ impl<T: Attribute> Buffer<T> {
     pub fn set_data(&self, data: Vec<T>) {
         self.handle.set_data(data);
     }
+
+    /// A counter that changes whenever `set_data` is called.
+    pub fn version(&self) -> u64 {
+        self.handle.version()
+    }
+
+    /// Return a copy of the data last set on the buffer, without touching
+    /// the GPU. This doesn't include GPU-side writes such as transform
+    /// feedback output; use `read_back` for those.
+    pub fn data(&self) -> Vec<T> {
+        let mut result = vec![T::zeroed(); self.handle.len()];
+        self.handle.copy_data(bytemuck::cast_slice_mut(&mut result));
+
+        result
+    }
+
+    /// Read the buffer's contents back from the GPU.
+    ///
+    /// This stalls until all queued GPU work has finished; prefer
+    /// `read_back_async` outside of tests and tools.
+    pub fn read_back(&self, renderer: &mut Renderer) -> Result<Vec<T>> {
+        let mut result = vec![T::zeroed(); self.handle.len()];
+        renderer
+            .gpu()
+            .read_buffer(&self.handle, bytemuck::cast_slice_mut(&mut result))?;
+
+        Ok(result)
+    }
+
+    /// Queue a read of the buffer's current contents without stalling the
+    /// pipeline. Poll the result once per frame until it is available.
+    pub fn read_back_async(&self, renderer: &mut Renderer) -> Result<PendingReadback<T>> {
+        let pending = renderer.gpu().read_buffer_async(&self.handle)?;
+
+        Ok(PendingReadback {
+            pending: Some(pending),
+            _ph: PhantomData,
+        })
+    }
+}
+
+/// A buffer readback which has been queued but may not have completed.
+pub struct PendingReadback<T: Attribute> {
+    pending: Option<PendingRead>,
+    _ph: PhantomData<T>,
+}
+
+impl<T: Attribute> PendingReadback<T> {
+    /// Returns the buffer contents if the GPU has finished copying them, or
+    /// `None` if it hasn't yet. Once data has been returned, the readback is
+    /// complete and polling again is an error.
+    pub fn poll(&mut self, renderer: &mut Renderer) -> Result<Option<Vec<T>>> {
+        let pending = self
+            .pending
+            .take()
+            .ok_or_else(|| Error::InvalidUsage("Readback was already completed.".to_string()))?;
+
+        if !renderer.gpu().is_read_ready(&pending)? {
+            self.pending = Some(pending);
+            return Ok(None);
+        }
+
+        let mut result = vec![T::zeroed(); pending.byte_len() / std::mem::size_of::<T>()];
+        renderer
+            .gpu()
+            .finish_read(pending, bytemuck::cast_slice_mut(&mut result));
+
+        Ok(Some(result))
+    }
 }
 
 impl<T: Attribute> BufferLike<T> for Buffer<T> {
@@ -44,6 +118,74 @@ impl<T: Attribute> BufferLike<T> for Buffer<T> {
     }
 }
 
+/// Vertex indices for `Renderer::render_indexed`, so that vertices shared
+/// between primitives are stored once.
+#[derive(Clone)]
+pub struct IndexBuffer {
+    handle: BufferHandle,
+}
+
+impl IndexBuffer {
+    pub fn new(data: Vec<u32>, usage_hint: BufferUsageHint) -> Self {
+        let handle = BufferHandle::new_index(usage_hint);
+        handle.set_data(data);
+
+        IndexBuffer { handle }
+    }
+
+    pub fn new_empty(usage_hint: BufferUsageHint) -> Self {
+        Self::new(Vec::new(), usage_hint)
+    }
+
+    pub fn set_data(&self, data: Vec<u32>) {
+        self.handle.set_data(data);
+    }
+
+    /// Return a copy of the indices last set on the buffer.
+    pub fn data(&self) -> Vec<u32> {
+        let mut result = vec![0; self.handle.len()];
+        self.handle.copy_data(bytemuck::cast_slice_mut(&mut result));
+
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.handle.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handle.is_empty()
+    }
+
+    pub(crate) fn handle(&self) -> &BufferHandle {
+        &self.handle
+    }
+}
+
+/// A buffer that can receive transform feedback output.
+pub trait FeedbackBuffer {
+    /// Ensure the buffer holds exactly `count` records, and return its handle.
+    fn feedback_handle(&self, count: usize) -> BufferHandle;
+
+    /// Describe the varyings captured into this buffer.
+    fn describe(&self) -> Vec<AttributeBinding>;
+}
+
+impl<T: Attribute> FeedbackBuffer for Buffer<T> {
+    fn feedback_handle(&self, count: usize) -> BufferHandle {
+        if self.handle.len() != count {
+            // The GPU will overwrite the contents, we just need the storage.
+            self.handle.set_data(vec![T::zeroed(); count]);
+        }
+
+        self.handle.clone()
+    }
+
+    fn describe(&self) -> Vec<AttributeBinding> {
+        T::describe()
+    }
+}
+
 pub struct DummyBuffer {
     size: usize,
 }
@@ -0,0 +1,221 @@
+use std::fmt::Display;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The shader stage a compile error occurred in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+impl Display for ShaderStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderStage::Vertex => write!(f, "vertex"),
+            ShaderStage::Fragment => write!(f, "fragment"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single message from a shader info log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderMessage {
+    pub severity: Severity,
+
+    /// The 1-based line of the shader source the message refers to, if any.
+    pub line: Option<u32>,
+
+    pub message: String,
+}
+
+impl ShaderMessage {
+    /// Parse a shader info log into messages.
+    ///
+    /// Browsers report messages in the form `ERROR: 0:12: 'x' : undeclared identifier`,
+    /// where `0` is the source string index and `12` is the line number. Lines
+    /// that don't follow that form are kept as messages without a line number.
+    pub fn parse_log(log: &str) -> Vec<ShaderMessage> {
+        log.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && *line != "\0")
+            .map(|line| {
+                let (severity, rest) = if let Some(rest) = line.strip_prefix("ERROR:") {
+                    (Severity::Error, rest)
+                } else if let Some(rest) = line.strip_prefix("WARNING:") {
+                    (Severity::Warning, rest)
+                } else {
+                    (Severity::Error, line)
+                };
+
+                let mut parts = rest.splitn(3, ':');
+                let source_index = parts.next().map(str::trim);
+                let line_number = parts.next().map(str::trim);
+                let message = parts.next().map(str::trim);
+
+                match (source_index, line_number, message) {
+                    (Some(source_index), Some(line_number), Some(message))
+                        if source_index.parse::<u32>().is_ok() =>
+                    {
+                        ShaderMessage {
+                            severity,
+                            line: line_number.parse().ok(),
+                            message: message.to_string(),
+                        }
+                    }
+                    _ => ShaderMessage {
+                        severity,
+                        line: None,
+                        message: rest.trim().to_string(),
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+impl Display for ShaderMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+
+        if let Some(line) = self.line {
+            write!(f, "{} on line {}: {}", severity, line, self.message)
+        } else {
+            write!(f, "{}: {}", severity, self.message)
+        }
+    }
+}
+
+/// Kinds of GL objects, used to report which one couldn't be created.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GlObject {
+    Buffer,
+    Framebuffer,
+    Program,
+    Query,
+    Renderbuffer,
+    Shader,
+    Sync,
+    Texture,
+    VertexArray,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// A shader failed to compile.
+    ShaderCompile {
+        stage: ShaderStage,
+        source: String,
+        log: String,
+        messages: Vec<ShaderMessage>,
+    },
+
+    /// A program failed to link.
+    ProgramLink {
+        log: String,
+    },
+
+    /// A uniform passed to `with_uniform` is not an active uniform of the
+    /// program. The GLSL compiler removes uniforms that don't affect output.
+    UniformNotFound {
+        name: String,
+    },
+
+    /// The WebGL context was lost. All GL objects have to be recreated once
+    /// it is restored.
+    ContextLost,
+
+    /// WebGL returned null when creating an object, typically because the
+    /// context was lost.
+    ObjectCreation(GlObject),
+
+    /// A GL enum value (such as an attribute or uniform type) that limelight
+    /// does not support.
+    UnsupportedType {
+        value: u32,
+    },
+
+    IncompleteFramebuffer {
+        status: u32,
+    },
+
+    /// An error code returned by `getError`. In debug mode, `context`
+    /// describes the call that raised it.
+    Gl {
+        code: u32,
+        context: Option<String>,
+    },
+
+    /// The API was used in a way that can't be satisfied, e.g. passing the
+    /// wrong number of transform feedback buffers.
+    InvalidUsage(String),
+
+    /// An exception thrown by a WebGL call.
+    Js(String),
+
+    /// An image could not be encoded or decoded.
+    Image(String),
+}
+
+impl Error {
+    pub(crate) fn from_js(value: wasm_bindgen::JsValue) -> Self {
+        Error::Js(format!("{:?}", value))
+    }
+}
+
+fn gl_error_name(code: u32) -> &'static str {
+    match code {
+        0x0500 => "INVALID_ENUM",
+        0x0501 => "INVALID_VALUE",
+        0x0502 => "INVALID_OPERATION",
+        0x0505 => "OUT_OF_MEMORY",
+        0x0506 => "INVALID_FRAMEBUFFER_OPERATION",
+        0x9242 => "CONTEXT_LOST_WEBGL",
+        _ => "unknown error",
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ShaderCompile {
+                stage, messages, ..
+            } => {
+                write!(f, "Error compiling {} shader", stage)?;
+                for message in messages {
+                    write!(f, "\n  {}", message)?;
+                }
+                Ok(())
+            }
+            Error::ProgramLink { log } => write!(f, "Error linking program: {}", log),
+            Error::UniformNotFound { name } => write!(f, "Uniform {} not found.", name),
+            Error::ContextLost => write!(f, "The WebGL context was lost."),
+            Error::ObjectCreation(kind) => write!(f, "Couldn't create {:?}.", kind),
+            Error::UnsupportedType { value } => write!(f, "Unsupported GL type: {:#x}", value),
+            Error::IncompleteFramebuffer { status } => {
+                write!(f, "Framebuffer is incomplete: {:#x}", status)
+            }
+            Error::Gl { code, context } => {
+                write!(f, "WebGL error {} ({:#x})", gl_error_name(*code), code)?;
+                if let Some(context) = context {
+                    write!(f, " in {}", context)?;
+                }
+                Ok(())
+            }
+            Error::InvalidUsage(message) => write!(f, "{}", message),
+            Error::Js(message) => write!(f, "JavaScript exception: {}", message),
+            Error::Image(message) => write!(f, "Image error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
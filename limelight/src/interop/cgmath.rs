@@ -0,0 +1,39 @@
+use crate::shadow_gpu::{UniformValue, UniformValueType};
+use cgmath::{Matrix, Matrix2, Matrix3, Matrix4, Point2, Point3, Vector2, Vector3, Vector4};
+
+macro_rules! impl_vector {
+    ($ty:ty, $variant:ident) => {
+        impl UniformValueType for $ty {
+            fn into_uniform_value(v: &$ty) -> UniformValue {
+                UniformValue::$variant((*v).into())
+            }
+        }
+    };
+}
+
+impl_vector!(Vector2<f32>, Vec2);
+impl_vector!(Vector3<f32>, Vec3);
+impl_vector!(Vector4<f32>, Vec4);
+impl_vector!(Point2<f32>, Vec2);
+impl_vector!(Point3<f32>, Vec3);
+impl_vector!(Vector2<i32>, IntVec2);
+impl_vector!(Vector3<i32>, IntVec3);
+impl_vector!(Vector4<i32>, IntVec4);
+impl_vector!(Vector2<u32>, UnsignedIntVec2);
+impl_vector!(Vector3<u32>, UnsignedIntVec3);
+impl_vector!(Vector4<u32>, UnsignedIntVec4);
+
+macro_rules! impl_matrix {
+    ($ty:ty, $variant:ident) => {
+        impl UniformValueType for $ty {
+            fn into_uniform_value(v: &$ty) -> UniformValue {
+                // See the module documentation for why matrices are transposed.
+                UniformValue::$variant(v.transpose().into())
+            }
+        }
+    };
+}
+
+impl_matrix!(Matrix2<f32>, Mat2);
+impl_matrix!(Matrix3<f32>, Mat3);
+impl_matrix!(Matrix4<f32>, Mat4);
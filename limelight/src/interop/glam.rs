@@ -0,0 +1,47 @@
+use crate::{
+    shadow_gpu::{UniformValue, UniformValueType},
+    webgl::types::{DataType, SizedDataType},
+    AsSizedDataType,
+};
+use glam::{IVec2, IVec3, IVec4, Mat2, Mat3, Mat4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+macro_rules! impl_vector {
+    ($ty:ty, $variant:ident, $data_type:ident, $size:expr) => {
+        impl UniformValueType for $ty {
+            fn into_uniform_value(v: &$ty) -> UniformValue {
+                UniformValue::$variant(v.to_array())
+            }
+        }
+
+        impl AsSizedDataType for $ty {
+            fn as_sized_data_type() -> SizedDataType {
+                SizedDataType::new(DataType::$data_type, $size)
+            }
+        }
+    };
+}
+
+impl_vector!(Vec2, Vec2, Float, 2);
+impl_vector!(Vec3, Vec3, Float, 3);
+impl_vector!(Vec4, Vec4, Float, 4);
+impl_vector!(IVec2, IntVec2, Int, 2);
+impl_vector!(IVec3, IntVec3, Int, 3);
+impl_vector!(IVec4, IntVec4, Int, 4);
+impl_vector!(UVec2, UnsignedIntVec2, UnsignedInt, 2);
+impl_vector!(UVec3, UnsignedIntVec3, UnsignedInt, 3);
+impl_vector!(UVec4, UnsignedIntVec4, UnsignedInt, 4);
+
+macro_rules! impl_matrix {
+    ($ty:ty, $variant:ident) => {
+        impl UniformValueType for $ty {
+            fn into_uniform_value(v: &$ty) -> UniformValue {
+                // See the module documentation for why matrices are transposed.
+                UniformValue::$variant(v.transpose().to_cols_array_2d())
+            }
+        }
+    };
+}
+
+impl_matrix!(Mat2, Mat2);
+impl_matrix!(Mat3, Mat3);
+impl_matrix!(Mat4, Mat4);
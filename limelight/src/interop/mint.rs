@@ -0,0 +1,48 @@
+use crate::shadow_gpu::{UniformValue, UniformValueType};
+use mint::{
+    ColumnMatrix2, ColumnMatrix3, ColumnMatrix4, RowMatrix2, RowMatrix3, RowMatrix4, Vector2,
+    Vector3, Vector4,
+};
+
+macro_rules! impl_vector {
+    ($ty:ty, $variant:ident) => {
+        impl UniformValueType for $ty {
+            fn into_uniform_value(v: &$ty) -> UniformValue {
+                UniformValue::$variant((*v).into())
+            }
+        }
+    };
+}
+
+impl_vector!(Vector2<f32>, Vec2);
+impl_vector!(Vector3<f32>, Vec3);
+impl_vector!(Vector4<f32>, Vec4);
+impl_vector!(Vector2<i32>, IntVec2);
+impl_vector!(Vector3<i32>, IntVec3);
+impl_vector!(Vector4<i32>, IntVec4);
+impl_vector!(Vector2<u32>, UnsignedIntVec2);
+impl_vector!(Vector3<u32>, UnsignedIntVec3);
+impl_vector!(Vector4<u32>, UnsignedIntVec4);
+
+// A row matrix converts to an array of its rows, which is what limelight
+// expects (see the module documentation). Column matrices are converted to
+// row matrices first.
+macro_rules! impl_matrix {
+    ($row:ty, $column:ty, $variant:ident) => {
+        impl UniformValueType for $row {
+            fn into_uniform_value(v: &$row) -> UniformValue {
+                UniformValue::$variant((*v).into())
+            }
+        }
+
+        impl UniformValueType for $column {
+            fn into_uniform_value(v: &$column) -> UniformValue {
+                UniformValue::$variant(<$row>::from(*v).into())
+            }
+        }
+    };
+}
+
+impl_matrix!(RowMatrix2<f32>, ColumnMatrix2<f32>, Mat2);
+impl_matrix!(RowMatrix3<f32>, ColumnMatrix3<f32>, Mat3);
+impl_matrix!(RowMatrix4<f32>, ColumnMatrix4<f32>, Mat4);
@@ -0,0 +1,33 @@
+//! Conversions from math library types, enabled with the `glam`,
+//! `nalgebra`, `mint` and `cgmath` cargo features.
+//!
+//! Vectors and matrices implement `UniformValueType`, so they can be used
+//! directly as `Uniform` values. Vector types that implement
+//! `bytemuck::Pod` (those from `glam` and `nalgebra`) also implement
+//! `AsSizedDataType`, so they can be used as fields of `#[attribute]`
+//! structs. `mint` and `cgmath` types don't implement `Pod`, so they can
+//! only be used as uniforms.
+//!
+//! # Matrix convention
+//!
+//! The primitive shaders apply transforms by multiplying a row vector by
+//! the matrix, as in `vec4(position, 0., 1.) * u_transform`. Math libraries
+//! apply matrices to column vectors, as in `matrix * vector`. Matrices from
+//! these libraries are transposed when they are uploaded, so that
+//! `vector * u_transform` in a shader computes the same thing as
+//! `matrix * vector` on the CPU.
+//!
+//! Plain `[[f32; 4]; 4]` uniforms are uploaded as-is. Each inner array is
+//! a row of the matrix as it is written, e.g. the translation is in the
+//! last element of each of the first three inner arrays. Converting a
+//! library matrix with `.into()` instead of using it directly yields its
+//! columns, which is the transpose of that convention.
+
+#[cfg(feature = "cgmath")]
+mod cgmath;
+#[cfg(feature = "glam")]
+mod glam;
+#[cfg(feature = "mint")]
+mod mint;
+#[cfg(feature = "nalgebra")]
+mod nalgebra;
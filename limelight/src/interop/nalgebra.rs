@@ -0,0 +1,49 @@
+use crate::{
+    shadow_gpu::{UniformValue, UniformValueType},
+    webgl::types::{DataType, SizedDataType},
+    AsSizedDataType,
+};
+use nalgebra::{Matrix2, Matrix3, Matrix4, Point2, Point3, Vector2, Vector3, Vector4};
+
+macro_rules! impl_vector {
+    ($ty:ty, $variant:ident, $data_type:ident, $size:expr) => {
+        impl UniformValueType for $ty {
+            fn into_uniform_value(v: &$ty) -> UniformValue {
+                UniformValue::$variant((*v).into())
+            }
+        }
+
+        impl AsSizedDataType for $ty {
+            fn as_sized_data_type() -> SizedDataType {
+                SizedDataType::new(DataType::$data_type, $size)
+            }
+        }
+    };
+}
+
+impl_vector!(Vector2<f32>, Vec2, Float, 2);
+impl_vector!(Vector3<f32>, Vec3, Float, 3);
+impl_vector!(Vector4<f32>, Vec4, Float, 4);
+impl_vector!(Point2<f32>, Vec2, Float, 2);
+impl_vector!(Point3<f32>, Vec3, Float, 3);
+impl_vector!(Vector2<i32>, IntVec2, Int, 2);
+impl_vector!(Vector3<i32>, IntVec3, Int, 3);
+impl_vector!(Vector4<i32>, IntVec4, Int, 4);
+impl_vector!(Vector2<u32>, UnsignedIntVec2, UnsignedInt, 2);
+impl_vector!(Vector3<u32>, UnsignedIntVec3, UnsignedInt, 3);
+impl_vector!(Vector4<u32>, UnsignedIntVec4, UnsignedInt, 4);
+
+macro_rules! impl_matrix {
+    ($ty:ty, $variant:ident) => {
+        impl UniformValueType for $ty {
+            fn into_uniform_value(v: &$ty) -> UniformValue {
+                // See the module documentation for why matrices are transposed.
+                UniformValue::$variant(v.transpose().into())
+            }
+        }
+    };
+}
+
+impl_matrix!(Matrix2<f32>, Mat2);
+impl_matrix!(Matrix3<f32>, Mat3);
+impl_matrix!(Matrix4<f32>, Mat4);
@@ -3,23 +3,37 @@
 mod attribute;
 pub mod buffer;
 pub mod draw_modes;
+pub mod error;
+pub mod interop;
+pub mod picking;
 pub mod program;
+pub mod render_target;
 pub mod renderer;
 pub mod shadow_gpu;
+pub mod snapshot;
 pub mod state;
+pub mod texture;
 pub mod uniform;
+pub mod vertex_binding;
 pub mod webgl;
 
 pub use bytemuck;
 pub use limelight_derive::{attribute, Attribute};
 
 pub use attribute::{Attribute, AttributeBinding};
-pub use buffer::{Buffer, DummyBuffer};
+pub use buffer::{Buffer, DummyBuffer, FeedbackBuffer, IndexBuffer, PendingReadback};
 pub use draw_modes::DrawMode;
+pub use error::{Error, Result};
+pub use picking::{LayerId, Pickable};
 pub use program::Program;
+pub use render_target::{PixelRect, RenderTarget};
 pub use renderer::Renderer;
-pub use uniform::Uniform;
-pub use webgl::buffer::{BufferBindPoint, BufferUsageHint};
+pub use snapshot::Snapshot;
+pub use texture::Texture;
+pub use uniform::{Uniform, UniformSource};
+pub use vertex_binding::VertexBinding;
+pub use webgl::buffer::{BufferBindPoint, BufferUsageHint, FeedbackBufferMode};
+pub use webgl::texture::{PixelFormat, ReadFormat, TextureKind};
 pub use webgl::types::AsSizedDataType;
 
 // #[allow(unused)]
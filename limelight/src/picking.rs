@@ -0,0 +1,31 @@
+//! Picking instances on the GPU, by drawing them into an offscreen integer
+//! target with each pixel set to the ID of the instance that covers it.
+//!
+//! Layers implement `Pickable` by drawing with a variant of their shaders
+//! that writes a `uvec4` of `(u_pick_id, gl_InstanceID, 0, 0)` instead of a
+//! color. The renderer supplies `u_pick_id` (a `uint`) during a picking pass
+//! with a non-zero value identifying the layer being drawn, so that pixels
+//! not covered by any instance can be told apart. `picking_source` defines
+//! `PICKING` in a shader, so that both variants can share one source.
+
+use crate::{Renderer, Result};
+
+/// Identifies a layer in the results of `Renderer::pick`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LayerId(pub u32);
+
+/// A layer that can draw itself into a picking pass started by
+/// `Renderer::render_picking`.
+pub trait Pickable {
+    fn draw_picking(&mut self, renderer: &mut Renderer) -> Result<()>;
+}
+
+/// Return `source` with `#define PICKING` added after its `#version` line.
+pub fn picking_source(source: &str) -> String {
+    match source.split_once('\n') {
+        Some((version, rest)) if version.starts_with("#version") => {
+            format!("{}\n#define PICKING\n{}", version, rest)
+        }
+        _ => format!("#define PICKING\n{}", source),
+    }
+}
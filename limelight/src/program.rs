@@ -1,13 +1,19 @@
-use anyhow::Result;
 use std::{
+    cell::Cell,
     collections::{hash_map::Entry, HashMap},
     marker::PhantomData,
 };
 
 use crate::{
-    shadow_gpu::{AttributeInfo, ProgramHandle, ShadowGpu, UniformHandle, UniformValueType},
+    error::Result,
+    shadow_gpu::{
+        AttributeInfo, ProgramHandle, ShadowGpu, TextureHandle, TransformFeedbackVaryings,
+        UniformHandle, UniformValue, UniformValueType,
+    },
     state::StateDescriptor,
+    texture::Texture,
     uniform::GenericUniform,
+    webgl::buffer::FeedbackBufferMode,
     Attribute, DrawMode, Uniform,
 };
 
@@ -19,16 +25,57 @@ pub trait ProgramLike<T: Attribute, I: Attribute> {
     fn draw_mode(&self) -> DrawMode;
 }
 
+/// A uniform set with `with_uniform`, along with the version of its value
+/// that was last uploaded to the program.
+///
+/// GL keeps uniform values per program, so a value only needs to be uploaded
+/// again when it changes, regardless of which programs were used in between.
+pub(crate) struct BoundUniform {
+    pub handle: UniformHandle,
+    pub uniform: Box<dyn GenericUniform>,
+    pub uploaded_version: Cell<Option<u64>>,
+}
+
+/// What was last uploaded for a `ProvidedUniform`.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Uploaded {
+    /// A version of a uniform registered on the renderer.
+    Version { entry: u64, version: u64 },
+
+    /// A value computed by the renderer, like `u_resolution`.
+    Value(UniformValue),
+}
+
+/// An active uniform that the program doesn't set with `with_uniform`, and
+/// so may be supplied by the renderer, along with what was last uploaded.
+pub(crate) struct ProvidedUniform {
+    pub name: String,
+    pub handle: UniformHandle,
+    pub uploaded: Cell<Option<Uploaded>>,
+}
+
 pub struct BoundProgram<T: Attribute, I: Attribute> {
     handle: ProgramHandle,
-    pub uniforms: Vec<(UniformHandle, Box<dyn GenericUniform>)>,
+    pub(crate) uniforms: Vec<BoundUniform>,
+
+    /// Active uniforms not set with `with_uniform`. The renderer fills these
+    /// in from its global uniforms, if one with the same name is registered.
+    pub(crate) provided_uniforms: Vec<ProvidedUniform>,
+
+    /// Textures set with `with_texture`, indexed by texture unit.
+    pub(crate) textures: Vec<TextureHandle>,
     draw_mode: DrawMode,
     state: StateDescriptor,
+    transform_feedback: Option<TransformFeedbackVaryings>,
     _ph: PhantomData<T>,
     _phi: PhantomData<I>,
 }
 
 impl<T: Attribute, I: Attribute> BoundProgram<T, I> {
+    pub fn label(&self) -> Option<&str> {
+        self.handle.label.as_deref()
+    }
+
     pub fn handle(&self) -> ProgramHandle {
         self.handle.clone()
     }
@@ -36,14 +83,21 @@ impl<T: Attribute, I: Attribute> BoundProgram<T, I> {
     pub fn attributes(&self) -> &HashMap<String, AttributeInfo> {
         &self.handle.attributes
     }
+
+    pub fn transform_feedback(&self) -> Option<&TransformFeedbackVaryings> {
+        self.transform_feedback.as_ref()
+    }
 }
 
 pub struct UnboundProgram<T: Attribute, I: Attribute> {
     fragment_shader_source: String,
     vertex_shader_source: String,
     uniforms: HashMap<String, Box<dyn GenericUniform>>,
+    textures: Vec<TextureHandle>,
     draw_mode: DrawMode,
     state: StateDescriptor,
+    transform_feedback: Option<TransformFeedbackVaryings>,
+    label: Option<String>,
     _ph: PhantomData<T>,
     _phi: PhantomData<I>,
 }
@@ -62,6 +116,15 @@ impl<T: Attribute, I: Attribute> UnboundProgram<T, I> {
         self
     }
 
+    pub fn with_texture(&mut self, name: &str, texture: &Texture) -> &mut Self {
+        // Sampler uniforms hold the index of the texture unit to read from.
+        let unit = self.textures.len() as i32;
+        self.with_uniform(name, Uniform::new(unit));
+        self.textures.push(texture.handle().clone());
+
+        self
+    }
+
     fn new_dummy() -> Self {
         UnboundProgram {
             _ph: PhantomData::default(),
@@ -69,34 +132,93 @@ impl<T: Attribute, I: Attribute> UnboundProgram<T, I> {
             fragment_shader_source: "".to_string(),
             vertex_shader_source: "".to_string(),
             uniforms: HashMap::new(),
+            textures: Vec::new(),
             state: StateDescriptor::default(),
             draw_mode: DrawMode::Triangles,
+            transform_feedback: None,
+            label: None,
         }
     }
 
     pub fn bind(self, gpu: &ShadowGpu) -> Result<BoundProgram<T, I>> {
+        let linked = self.link(gpu)?;
+        Ok(self.into_bound(linked))
+    }
+
+    /// Create the GL objects for this program without consuming it, so that
+    /// it can be bound again if this fails.
+    fn link(&self, gpu: &ShadowGpu) -> Result<LinkedProgram> {
         let vertex_shader = gpu.compile_vertex_shader(&self.vertex_shader_source)?;
         let fragment_shader = gpu.compile_fragment_shader(&self.fragment_shader_source)?;
-        let program = gpu.link_program(&fragment_shader, &vertex_shader)?;
+        let mut program = gpu.link_program(
+            &fragment_shader,
+            &vertex_shader,
+            self.transform_feedback.as_ref(),
+        )?;
+        program.label = self.label.clone();
 
-        let mut bound_uniforms = Vec::with_capacity(self.uniforms.len());
+        let provided_uniforms = gpu
+            .active_uniform_names(&program)
+            .into_iter()
+            .filter(|name| !self.uniforms.contains_key(name))
+            .filter_map(|name| {
+                // Members of uniform blocks are active, but have no location.
+                let handle = gpu.get_uniform_handle(&program, &name).ok()?;
+                Some(ProvidedUniform {
+                    name,
+                    handle,
+                    uploaded: Cell::new(None),
+                })
+            })
+            .collect();
 
-        for (name, uniform) in self.uniforms {
-            let loc = gpu.get_uniform_handle(&program, &name)?;
-            bound_uniforms.push((loc, uniform));
+        let mut uniform_handles = HashMap::with_capacity(self.uniforms.len());
+        for name in self.uniforms.keys() {
+            uniform_handles.insert(name.clone(), gpu.get_uniform_handle(&program, name)?);
         }
 
-        Ok(BoundProgram {
+        Ok(LinkedProgram {
             handle: program,
+            provided_uniforms,
+            uniform_handles,
+        })
+    }
+
+    fn into_bound(self, mut linked: LinkedProgram) -> BoundProgram<T, I> {
+        let bound_uniforms = self
+            .uniforms
+            .into_iter()
+            .map(|(name, uniform)| BoundUniform {
+                handle: linked
+                    .uniform_handles
+                    .remove(&name)
+                    .expect("Uniform was linked."),
+                uniform,
+                uploaded_version: Cell::new(None),
+            })
+            .collect();
+
+        BoundProgram {
+            handle: linked.handle,
             uniforms: bound_uniforms,
+            provided_uniforms: linked.provided_uniforms,
+            textures: self.textures,
             draw_mode: self.draw_mode,
             state: self.state,
+            transform_feedback: self.transform_feedback,
             _ph: PhantomData::default(),
             _phi: PhantomData::default(),
-        })
+        }
     }
 }
 
+/// The GL objects created for an `UnboundProgram` by `link`.
+struct LinkedProgram {
+    handle: ProgramHandle,
+    provided_uniforms: Vec<ProvidedUniform>,
+    uniform_handles: HashMap<String, UniformHandle>,
+}
+
 pub enum Program<T: Attribute, I: Attribute> {
     Unbound(UnboundProgram<T, I>),
     Bound(BoundProgram<T, I>),
@@ -112,8 +234,11 @@ impl<T: Attribute, I: Attribute> Program<T, I> {
             fragment_shader_source: fragment_shader_source.to_string(),
             vertex_shader_source: vertex_shader_source.to_string(),
             uniforms: HashMap::new(),
+            textures: Vec::new(),
             draw_mode,
             state: StateDescriptor::default(),
+            transform_feedback: None,
+            label: None,
             _ph: PhantomData::default(),
             _phi: PhantomData::default(),
         })
@@ -135,6 +260,8 @@ impl<T: Attribute, I: Attribute> ProgramLike<T, I> for BoundProgram<T, I> {
 }
 
 impl<T: Attribute, I: Attribute> Program<T, I> {
+    /// Set a uniform of this program. This takes precedence over a global
+    /// uniform of the same name registered on the `Renderer`.
     pub fn with_uniform<U: UniformValueType>(mut self, name: &str, uniform: Uniform<U>) -> Self {
         match &mut self {
             Program::Bound(_) => {
@@ -148,6 +275,22 @@ impl<T: Attribute, I: Attribute> Program<T, I> {
         self
     }
 
+    /// Bind a texture to a sampler uniform of this program, like `sampler2D`,
+    /// `sampler2DArray` or `sampler3D`. Each texture is given its own
+    /// texture unit, in the order they are added.
+    pub fn with_texture(mut self, name: &str, texture: &Texture) -> Self {
+        match &mut self {
+            Program::Bound(_) => {
+                panic!("Tried calling with_texture on a program that is already bound.")
+            }
+            Program::Unbound(p) => {
+                p.with_texture(name, texture);
+            }
+        }
+
+        self
+    }
+
     pub fn with_state(mut self, state: StateDescriptor) -> Self {
         match &mut self {
             Program::Bound(_) => {
@@ -160,26 +303,66 @@ impl<T: Attribute, I: Attribute> Program<T, I> {
 
         self
     }
-}
 
-impl<T: Attribute, I: Attribute> ProgramLike<T, I> for Program<T, I> {
-    fn get_program(&mut self, gpu: &ShadowGpu) -> Result<&BoundProgram<T, I>> {
-        match self {
-            Program::Bound(p) => Ok(p),
+    /// Name the program, so that errors in debug mode can say which layer
+    /// they came from.
+    pub fn with_label(mut self, label: &str) -> Self {
+        match &mut self {
+            Program::Bound(_) => {
+                panic!("Tried calling with_label on a program that is already bound.")
+            }
             Program::Unbound(p) => {
-                let mut dummy_program = UnboundProgram::new_dummy();
-                std::mem::swap(&mut dummy_program, p);
+                p.label = Some(label.to_string());
+            }
+        }
 
-                let result = dummy_program.bind(gpu)?;
-                *self = Program::Bound(result);
+        self
+    }
 
-                match self {
-                    Program::Bound(result) => Ok(result),
-                    _ => panic!(),
-                }
+    /// Capture the given vertex shader outputs with transform feedback when
+    /// this program is drawn with `Renderer::render_feedback`.
+    pub fn with_transform_feedback(mut self, varyings: &[&str], mode: FeedbackBufferMode) -> Self {
+        match &mut self {
+            Program::Bound(_) => {
+                panic!("Tried calling with_transform_feedback on a program that is already bound.")
             }
+            Program::Unbound(p) => {
+                p.transform_feedback = Some(TransformFeedbackVaryings {
+                    varyings: varyings.iter().map(|v| v.to_string()).collect(),
+                    mode,
+                });
+            }
+        }
+
+        self
+    }
+}
+
+impl<T: Attribute, I: Attribute> Program<T, I> {
+    /// Bind the program in place, using `link` to create its GL objects. If
+    /// that fails, the program is left unbound, so that drawing it again
+    /// (e.g. after the context is restored) retries.
+    fn bind_with(
+        &mut self,
+        link: impl FnOnce(&UnboundProgram<T, I>) -> Result<LinkedProgram>,
+    ) -> Result<&BoundProgram<T, I>> {
+        if let Program::Unbound(p) = self {
+            let linked = link(p)?;
+            let unbound = std::mem::replace(p, UnboundProgram::new_dummy());
+            *self = Program::Bound(unbound.into_bound(linked));
+        }
+
+        match self {
+            Program::Bound(p) => Ok(p),
+            Program::Unbound(_) => unreachable!(),
         }
     }
+}
+
+impl<T: Attribute, I: Attribute> ProgramLike<T, I> for Program<T, I> {
+    fn get_program(&mut self, gpu: &ShadowGpu) -> Result<&BoundProgram<T, I>> {
+        self.bind_with(|p| p.link(gpu))
+    }
 
     fn globals(&self) -> StateDescriptor {
         match self {
@@ -195,3 +378,29 @@ impl<T: Attribute, I: Attribute> ProgramLike<T, I> for Program<T, I> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_failed_bind_leaves_program_unbound() {
+        let mut program: Program<(), ()> = Program::new("vertex", "fragment", DrawMode::Triangles)
+            .with_uniform("u_scale", Uniform::new(2.0f32))
+            .with_label("test");
+
+        let result = program.bind_with(|_| Err(Error::ContextLost));
+        assert!(matches!(result, Err(Error::ContextLost)));
+
+        match &program {
+            Program::Unbound(p) => {
+                assert_eq!("vertex", p.vertex_shader_source);
+                assert_eq!("fragment", p.fragment_shader_source);
+                assert!(p.uniforms.contains_key("u_scale"));
+                assert_eq!(Some("test"), p.label.as_deref());
+            }
+            Program::Bound(_) => panic!("Expected the program to stay unbound."),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{
+    error::{Error, Result},
+    shadow_gpu::{ColorAttachment, FramebufferHandle, RenderbufferHandle, TextureHandle},
+    webgl::texture::PixelFormat,
+};
+
+/// An offscreen color buffer that can be rendered into instead of the canvas.
+///
+/// Like buffers, the GL objects backing a render target are created lazily
+/// the first time it is rendered into.
+///
+/// Multisampled targets, created with `new_multisampled`, are drawn to the
+/// same way but can't be read from directly. Use `Renderer::resolve` to
+/// copy them into a regular target or the canvas first.
+#[derive(Clone)]
+pub struct RenderTarget {
+    framebuffer: FramebufferHandle,
+}
+
+impl RenderTarget {
+    pub fn new(width: i32, height: i32, format: PixelFormat) -> Self {
+        let texture = TextureHandle::new(width, height, format);
+
+        RenderTarget {
+            framebuffer: FramebufferHandle::new(ColorAttachment::Texture(texture)),
+        }
+    }
+
+    /// Create a multisampled target. `samples` is clamped to the
+    /// `MAX_SAMPLES` supported by the browser when the target is first used.
+    /// WebGL doesn't support multisampling integer formats.
+    pub fn new_multisampled(
+        width: i32,
+        height: i32,
+        format: PixelFormat,
+        samples: i32,
+    ) -> Result<Self> {
+        if format.is_integer() {
+            return Err(Error::InvalidUsage(format!(
+                "Integer format {:?} can't be multisampled.",
+                format
+            )));
+        }
+
+        let renderbuffer = RenderbufferHandle::new(width, height, format, samples);
+
+        Ok(RenderTarget {
+            framebuffer: FramebufferHandle::new(ColorAttachment::Multisampled(renderbuffer)),
+        })
+    }
+
+    pub fn width(&self) -> i32 {
+        self.framebuffer.color().width()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.framebuffer.color().height()
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.framebuffer.color().format()
+    }
+
+    /// The requested sample count, or 0 if the target isn't multisampled.
+    pub fn samples(&self) -> i32 {
+        match self.framebuffer.color() {
+            ColorAttachment::Texture(_) => 0,
+            ColorAttachment::Multisampled(renderbuffer) => renderbuffer.samples(),
+        }
+    }
+
+    pub fn is_multisampled(&self) -> bool {
+        self.framebuffer.color().is_multisampled()
+    }
+
+    pub fn rect(&self) -> PixelRect {
+        PixelRect::new(0, 0, self.width(), self.height())
+    }
+
+    pub(crate) fn handle(&self) -> FramebufferHandle {
+        self.framebuffer.clone()
+    }
+}
+
+/// A rectangle of pixels, measured from the lower-left corner as in GL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl PixelRect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        PixelRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
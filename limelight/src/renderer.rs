@@ -2,15 +2,38 @@ use std::collections::{BTreeMap, HashMap};
 
 use crate::{
     attribute::Attribute,
-    buffer::BufferLike,
-    program::ProgramLike,
-    shadow_gpu::{AttributeInfo, BufferBinding, BufferHandle, GpuState, ShadowGpu},
+    buffer::{BufferLike, FeedbackBuffer, IndexBuffer},
+    error::{Error, Result},
+    picking::{LayerId, Pickable},
+    program::{ProgramLike, Uploaded},
+    render_target::{PixelRect, RenderTarget},
+    shadow_gpu::{
+        AttributeInfo, BufferBinding, BufferHandle, FramebufferHandle, GpuState, ShadowGpu,
+        TimedSection, UniformValue, UniformValueType, VertexArray,
+    },
+    snapshot::Snapshot,
+    uniform::{GlobalUniforms, UniformSource},
+    vertex_binding::VertexBinding,
+    webgl::{
+        buffer::FeedbackBufferMode,
+        texture::{PixelFormat, ReadFormat},
+    },
+    Uniform,
 };
-use anyhow::Result;
-use web_sys::WebGl2RenderingContext;
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext};
 
 pub struct Renderer {
     gpu: ShadowGpu,
+    target: Option<FramebufferHandle>,
+    viewport: Option<PixelRect>,
+    pixel_ratio: f64,
+    globals: GlobalUniforms,
+
+    /// The target of the last picking pass, the size of the canvas.
+    picking_target: Option<RenderTarget>,
+
+    /// The value of `u_pick_id` while a picking pass is drawing a layer.
+    pick_id: Option<u32>,
 }
 
 enum DrawCall {
@@ -23,22 +46,31 @@ enum DrawCall {
         count: usize,
         instances: usize,
     },
+    DrawElements {
+        count: usize,
+        indices: BufferHandle,
+    },
+    TransformFeedback {
+        first: usize,
+        count: usize,
+        outputs: Vec<BufferHandle>,
+    },
 }
 
-struct BufferBindingGroup {
-    bindings: BTreeMap<BufferHandle, Vec<BufferBinding>>,
+pub(crate) struct BufferBindingGroup {
+    pub(crate) bindings: BTreeMap<BufferHandle, Vec<BufferBinding>>,
     attributes: HashMap<String, AttributeInfo>,
 }
 
 impl BufferBindingGroup {
-    fn new(attributes: HashMap<String, AttributeInfo>) -> Self {
+    pub(crate) fn new(attributes: HashMap<String, AttributeInfo>) -> Self {
         Self {
             attributes,
             bindings: BTreeMap::new(),
         }
     }
 
-    fn add_buffer<T: Attribute>(&mut self, buffer: &impl BufferLike<T>, divisor: u32) {
+    pub(crate) fn add_buffer<T: Attribute>(&mut self, buffer: &impl BufferLike<T>, divisor: u32) {
         if let Some(buffer) = buffer.get_buffer() {
             let stride = T::describe().into_iter().map(|d| d.kind.byte_size()).sum();
             let mut offset = 0;
@@ -83,10 +115,293 @@ impl BufferBindingGroup {
     }
 }
 
+impl UniformSource for Renderer {
+    fn uniform_value(&self, name: &str) -> Option<UniformValue> {
+        Renderer::uniform_value(self, name)
+    }
+}
+
 impl Renderer {
     pub fn new(gl: WebGl2RenderingContext) -> Self {
         let gpu = ShadowGpu::new(gl);
-        Renderer { gpu }
+        let mut globals = GlobalUniforms::default();
+
+        // Programs that declare a transform without setting one shouldn't
+        // get GL's default all-zero matrix.
+        globals.insert("u_transform", Box::new(Uniform::identity()));
+
+        Renderer {
+            gpu,
+            target: None,
+            viewport: None,
+            pixel_ratio: 1.,
+            globals,
+            picking_target: None,
+            pick_id: None,
+        }
+    }
+
+    pub(crate) fn gpu(&mut self) -> &mut ShadowGpu {
+        &mut self.gpu
+    }
+
+    /// Enable or disable debug mode.
+    ///
+    /// In debug mode, `getError` is checked after each GL call made while
+    /// drawing, and failures are returned as an `Error::Gl` describing the
+    /// call, the program (by its `Program::with_label` label) and the
+    /// attribute binding involved. Program attributes without a buffer are
+    /// also reported. Checking for errors stalls the GPU pipeline, so this
+    /// should only be used during development.
+    pub fn set_debug(&mut self, debug: bool) {
+        self.gpu.set_debug(debug);
+    }
+
+    /// Register a uniform by name, to be supplied to every program that
+    /// declares it. Update it through the `Uniform`, e.g. to set `u_time`
+    /// once per frame. Programs can override it with `with_uniform`.
+    ///
+    /// Without a registered uniform of the same name, the renderer supplies
+    /// `u_resolution` (the viewport size in CSS pixels, as a `vec2`) and
+    /// `u_pixel_ratio` (a `float`), and `u_transform` defaults to the identity.
+    /// During `render_picking`, `u_pick_id` (a `uint`) identifies the layer
+    /// being drawn.
+    pub fn set_global_uniform<U: UniformValueType>(&mut self, name: &str, uniform: Uniform<U>) {
+        self.globals.insert(name, Box::new(uniform));
+    }
+
+    /// Unregister a global uniform. Returns `false` if none was registered
+    /// under `name`.
+    pub fn remove_global_uniform(&mut self, name: &str) -> bool {
+        self.globals.remove(name)
+    }
+
+    /// The value a program declaring the uniform `name` would receive from
+    /// the renderer: a registered global uniform's current value (including
+    /// the default `u_transform`), or a built-in one such as `u_resolution`.
+    /// Lets layers do CPU work that depends on them, like choosing a level
+    /// of detail.
+    pub fn uniform_value(&self, name: &str) -> Option<UniformValue> {
+        match self.globals.get(name) {
+            Some((_, global)) => Some(global.get_value()),
+            None => self.builtin_uniform(name, self.viewport()),
+        }
+    }
+
+    /// The value of a uniform computed by the renderer, if `name` is one.
+    fn builtin_uniform(&self, name: &str, viewport: PixelRect) -> Option<UniformValue> {
+        match name {
+            "u_resolution" => Some(UniformValue::Vec2([
+                (viewport.width as f64 / self.pixel_ratio) as f32,
+                (viewport.height as f64 / self.pixel_ratio) as f32,
+            ])),
+            "u_pixel_ratio" => Some(UniformValue::Float(self.pixel_ratio as f32)),
+            "u_pick_id" => self.pick_id.map(UniformValue::UnsignedInt),
+            _ => None,
+        }
+    }
+
+    /// Resize the canvas to the given size in CSS pixels, with a drawing
+    /// buffer scaled by the device pixel ratio, and reset the viewport to
+    /// cover it.
+    ///
+    /// Programs that declare a `vec2 u_resolution` uniform receive the
+    /// viewport size in CSS pixels (see `set_global_uniform`), so that
+    /// shaders can convert between CSS pixels and clip space.
+    pub fn resize(&mut self, css_width: f64, css_height: f64, pixel_ratio: f64) -> Result<()> {
+        let canvas: HtmlCanvasElement = self.gpu.canvas().ok_or_else(|| {
+            Error::InvalidUsage("Renderer::resize requires an HTML canvas.".to_string())
+        })?;
+
+        let width = (css_width * pixel_ratio).round() as u32;
+        let height = (css_height * pixel_ratio).round() as u32;
+
+        // Setting the size clears the canvas, even if it didn't change.
+        if canvas.width() != width || canvas.height() != height {
+            canvas.set_width(width);
+            canvas.set_height(height);
+        }
+
+        self.pixel_ratio = pixel_ratio;
+        self.viewport = None;
+
+        Ok(())
+    }
+
+    /// The ratio of drawing buffer pixels to CSS pixels, as passed to `resize`.
+    pub fn pixel_ratio(&self) -> f64 {
+        self.pixel_ratio
+    }
+
+    /// Restrict drawing to a rectangle of the current render target (or the
+    /// canvas). `None` covers the whole target.
+    pub fn set_viewport(&mut self, viewport: Option<PixelRect>) {
+        self.viewport = viewport;
+    }
+
+    /// The current viewport, in pixels of the current render target.
+    pub fn viewport(&self) -> PixelRect {
+        if let Some(viewport) = self.viewport {
+            viewport
+        } else {
+            let (width, height) = self.gpu.framebuffer_size(&self.target);
+            PixelRect::new(0, 0, width, height)
+        }
+    }
+
+    /// Direct subsequent draws, clears and reads to a render target, or back
+    /// to the canvas if `None`.
+    pub fn set_render_target(&mut self, target: Option<&RenderTarget>) {
+        self.target = target.map(RenderTarget::handle);
+    }
+
+    /// Clear the current render target to a color. For unsigned integer
+    /// targets, use `clear_integer` instead.
+    pub fn clear(&mut self, color: [f32; 4]) -> Result<()> {
+        self.gpu.clear(&self.target, color)
+    }
+
+    /// Clear the current unsigned integer render target to the given values.
+    pub fn clear_integer(&mut self, values: [u32; 4]) -> Result<()> {
+        self.gpu.clear_integer(&self.target, values)
+    }
+
+    /// Resolve a multisampled render target into a regular target, or into
+    /// the canvas if `destination` is `None`. Both must be the same size.
+    ///
+    /// Resolving into the canvas requires it to have been created with
+    /// `antialias: false`, since WebGL can't blit between two multisampled
+    /// framebuffers.
+    pub fn resolve(
+        &mut self,
+        source: &RenderTarget,
+        destination: Option<&RenderTarget>,
+    ) -> Result<()> {
+        self.gpu
+            .blit_framebuffer(&source.handle(), &destination.map(RenderTarget::handle))
+    }
+
+    /// Read pixels from the current render target (or the canvas).
+    ///
+    /// Rows are returned bottom-to-top. Use `ReadFormat::Rgba` for the canvas
+    /// and normalized targets, and `ReadFormat::RgbaInteger` for unsigned
+    /// integer targets.
+    pub fn read_pixels(&mut self, rect: PixelRect, format: ReadFormat) -> Result<Vec<u8>> {
+        self.gpu.read_pixels(
+            &self.target,
+            (rect.x, rect.y, rect.width, rect.height),
+            format,
+        )
+    }
+
+    /// Capture the full contents of the current render target (or the
+    /// canvas) as an RGBA image.
+    ///
+    /// Unless the canvas was created with `preserveDrawingBuffer`, it is
+    /// cleared after being composited, so snapshots of the canvas should be
+    /// taken in the same frame as the draw calls.
+    pub fn snapshot(&mut self) -> Result<Snapshot> {
+        if let Some(target) = &self.target {
+            if target.color().format().is_integer() {
+                return Err(Error::InvalidUsage(
+                    "Can't take a snapshot of an integer render target.".to_string(),
+                ));
+            }
+        }
+
+        let (width, height) = self.gpu.framebuffer_size(&self.target);
+        let data = self.read_pixels(PixelRect::new(0, 0, width, height), ReadFormat::Rgba)?;
+
+        Ok(Snapshot::from_gl_rows(width as _, height as _, data))
+    }
+
+    /// Draw layers into an offscreen picking target, for `pick` to read.
+    ///
+    /// The target covers the canvas, and the current viewport is used, so
+    /// the layers should be drawn with the same transforms as when they were
+    /// last drawn to the canvas. Where instances overlap, the one drawn last
+    /// is picked.
+    pub fn render_picking(&mut self, layers: &mut [(LayerId, &mut dyn Pickable)]) -> Result<()> {
+        let (width, height) = self.gpu.framebuffer_size(&None);
+        let target = match &self.picking_target {
+            Some(target) if (target.width(), target.height()) == (width, height) => target.clone(),
+            _ => {
+                let target = RenderTarget::new(width, height, PixelFormat::Rg32ui);
+                self.picking_target = Some(target.clone());
+                target
+            }
+        };
+
+        let previous_target = self.target.replace(target.handle());
+        let result = self.clear_integer([0; 4]).and_then(|_| {
+            for (LayerId(id), layer) in layers.iter_mut() {
+                // Zero is left for pixels that no instance covers.
+                self.pick_id = Some(id.checked_add(1).ok_or_else(|| {
+                    Error::InvalidUsage("LayerId(u32::MAX) can't be picked.".to_string())
+                })?);
+                layer.draw_picking(self)?;
+            }
+
+            Ok(())
+        });
+
+        self.pick_id = None;
+        self.target = previous_target;
+        result
+    }
+
+    /// Return the layer and instance index drawn at a point by the last
+    /// `render_picking` pass, or `None` if there is nothing there. The point
+    /// is in CSS pixels from the top left of the canvas, as in mouse events.
+    pub fn pick(&mut self, x: f64, y: f64) -> Result<Option<(LayerId, usize)>> {
+        let target = self.picking_target.clone().ok_or_else(|| {
+            Error::InvalidUsage("Renderer::pick requires a render_picking pass.".to_string())
+        })?;
+
+        let px = (x * self.pixel_ratio).floor() as i32;
+        let py = target.height() - 1 - (y * self.pixel_ratio).floor() as i32;
+        if !(0..target.width()).contains(&px) || !(0..target.height()).contains(&py) {
+            return Ok(None);
+        }
+
+        let data = self.gpu.read_pixels(
+            &Some(target.handle()),
+            (px, py, 1, 1),
+            ReadFormat::RgbaInteger,
+        )?;
+        let [pick_id, instance, _, _]: [u32; 4] = bytemuck::pod_read_unaligned(&data);
+
+        Ok(match pick_id {
+            0 => None,
+            id => Some((LayerId(id - 1), instance as usize)),
+        })
+    }
+
+    /// Opt in to GPU timing of sections marked with `begin_timed_section` and
+    /// `end_timed_section`. Returns `false` if the browser does not support
+    /// `EXT_disjoint_timer_query_webgl2`, in which case timed sections are no-ops.
+    pub fn enable_timer_queries(&mut self) -> bool {
+        self.gpu.enable_timer_queries()
+    }
+
+    /// Start timing GPU work under the given label. Sections cannot be nested.
+    pub fn begin_timed_section(&mut self, label: &str) -> Result<()> {
+        self.gpu.begin_timed_section(label)
+    }
+
+    /// Stop timing the section started by the last `begin_timed_section`. Its
+    /// result is reported by a later `poll_timed_sections`. If no section is
+    /// open, this logs a warning and does nothing.
+    pub fn end_timed_section(&mut self) {
+        self.gpu.end_timed_section()
+    }
+
+    /// Return the timings of sections whose results have become available.
+    ///
+    /// Results typically lag a few frames behind the draw calls they measure,
+    /// so this should be called once per frame.
+    pub fn poll_timed_sections(&mut self) -> Vec<TimedSection> {
+        self.gpu.poll_timed_sections()
     }
 
     fn render_impl<T: Attribute, I: Attribute>(
@@ -94,38 +409,96 @@ impl Renderer {
         draw_call: DrawCall,
         program: &mut impl ProgramLike<T, I>,
 
-        buffers: BTreeMap<BufferHandle, Vec<BufferBinding>>,
+        vertex_array: VertexArray,
     ) -> Result<()> {
+        self.gpu.check_context()?;
+        let globals = program.globals();
+        let draw_mode = program.draw_mode();
         let bound_program = program.get_program(&self.gpu)?;
 
-        let mut uniforms = HashMap::new();
-        for (uniform_handle, uniform) in &bound_program.uniforms {
-            uniforms.insert(uniform_handle.clone(), uniform.get_value());
+        let viewport = self.viewport();
+
+        // Only uniforms that changed since they were last uploaded to this
+        // program are sent, so unchanged ones are neither read nor copied.
+        let mut uniforms = Vec::new();
+        let mut uploaded_versions = Vec::new();
+        for uniform in &bound_program.uniforms {
+            let version = uniform.uniform.version();
+            if uniform.uploaded_version.get() != Some(version) {
+                uniforms.push((uniform.handle.clone(), uniform.uniform.get_value()));
+                uploaded_versions.push((&uniform.uploaded_version, version));
+            }
+        }
+
+        let mut uploaded_provided = Vec::new();
+        for uniform in &bound_program.provided_uniforms {
+            let (uploaded, value) = if let Some((entry, global)) = self.globals.get(&uniform.name) {
+                let uploaded = Uploaded::Version {
+                    entry,
+                    version: global.version(),
+                };
+                if uniform.uploaded.get() == Some(uploaded) {
+                    continue;
+                }
+                (uploaded, global.get_value())
+            } else if let Some(value) = self.builtin_uniform(&uniform.name, viewport) {
+                let uploaded = Uploaded::Value(value);
+                if uniform.uploaded.get() == Some(uploaded) {
+                    continue;
+                }
+                (uploaded, value)
+            } else {
+                continue;
+            };
+
+            uniforms.push((uniform.handle.clone(), value));
+            uploaded_provided.push((&uniform.uploaded, uploaded));
         }
 
         let state: GpuState = GpuState {
             program: Some(bound_program.handle()),
-            buffers,
+            vertex_array,
             uniforms,
-            globals: program.globals(),
+            textures: bound_program.textures.clone(),
+            globals,
+            framebuffer: self.target.clone(),
+            viewport: (viewport.x, viewport.y, viewport.width, viewport.height),
         };
 
         match draw_call {
-            DrawCall::DrawArrays { count, first } => {
-                self.gpu
-                    .draw_arrays(&state, program.draw_mode(), first as _, count as _)?
-            }
+            DrawCall::DrawArrays { count, first } => self
+                .gpu
+                .draw_arrays(&state, draw_mode, first as _, count as _)?,
             DrawCall::DrawArraysInstanced {
                 first,
                 count,
                 instances,
             } => self.gpu.draw_arrays_instanced(
                 &state,
-                program.draw_mode(),
+                draw_mode,
                 first as _,
                 count as _,
                 instances as _,
             )?,
+            DrawCall::DrawElements { count, indices } => self
+                .gpu
+                .draw_elements(&state, draw_mode, count as _, &indices)?,
+            DrawCall::TransformFeedback {
+                first,
+                count,
+                outputs,
+            } => self
+                .gpu
+                .draw_arrays_feedback(&state, draw_mode, first as _, count as _, &outputs)?,
+        }
+
+        // Record what was uploaded only once the draw succeeded, so that a
+        // failed draw doesn't leave the program with stale values.
+        for (cell, version) in uploaded_versions {
+            cell.set(Some(version));
+        }
+        for (cell, uploaded) in uploaded_provided {
+            cell.set(Some(uploaded));
         }
 
         Ok(())
@@ -148,7 +521,7 @@ impl Renderer {
                 count: vertex_buffer.len(),
             },
             program,
-            bg.bindings,
+            VertexArray::Bindings(bg.bindings),
         )
     }
 
@@ -172,7 +545,130 @@ impl Renderer {
                 instances: instance_buffer.len(),
             },
             program,
-            bg.bindings,
+            VertexArray::Bindings(bg.bindings),
+        )
+    }
+
+    /// Draw the vertices of `vertex_buffer` in the order given by
+    /// `index_buffer`.
+    pub fn render_indexed<T: Attribute>(
+        &mut self,
+        program: &mut impl ProgramLike<T, ()>,
+        vertex_buffer: &impl BufferLike<T>,
+        index_buffer: &IndexBuffer,
+    ) -> Result<()> {
+        let bound_program = program.get_program(&self.gpu)?;
+        let program_attributes = bound_program.attributes();
+
+        let mut bg = BufferBindingGroup::new(program_attributes.clone());
+        bg.add_buffer(vertex_buffer, 0);
+
+        self.render_impl(
+            DrawCall::DrawElements {
+                count: index_buffer.len(),
+                indices: index_buffer.handle().clone(),
+            },
+            program,
+            VertexArray::Bindings(bg.bindings),
+        )
+    }
+
+    /// Draw using a `VertexBinding` created for this program, skipping the
+    /// per-draw construction and lookup of attribute bindings. If the
+    /// binding has an instance buffer, the draw is instanced.
+    pub fn render_binding<T: Attribute, I: Attribute>(
+        &mut self,
+        program: &mut impl ProgramLike<T, I>,
+        binding: &VertexBinding<T, I>,
+    ) -> Result<()> {
+        let bound_program = program.get_program(&self.gpu)?;
+        if bound_program.handle() != *binding.program() {
+            return Err(Error::InvalidUsage(
+                "The vertex binding was created for a different program.".to_string(),
+            ));
+        }
+
+        let draw_call = if let Some(instances) = binding.instance_count() {
+            DrawCall::DrawArraysInstanced {
+                first: 0,
+                count: binding.vertex_count(),
+                instances,
+            }
+        } else {
+            DrawCall::DrawArrays {
+                first: 0,
+                count: binding.vertex_count(),
+            }
+        };
+
+        self.render_impl(draw_call, program, VertexArray::Owned(binding.vao()))
+    }
+
+    /// Run a program with transform feedback, capturing the varyings given to
+    /// `Program::with_transform_feedback` into `outputs`.
+    ///
+    /// In interleaved mode, `outputs` should contain a single buffer whose
+    /// attribute struct has a field for each varying, in order. In separate
+    /// mode, it should contain one buffer per varying. Each output buffer is
+    /// resized to hold one record per vertex processed.
+    ///
+    /// To skip rasterization entirely, set `rasterizer_discard` in the
+    /// program's `StateDescriptor`.
+    pub fn render_feedback<T: Attribute>(
+        &mut self,
+        program: &mut impl ProgramLike<T, ()>,
+        vertex_buffer: &impl BufferLike<T>,
+        outputs: &[&dyn FeedbackBuffer],
+    ) -> Result<()> {
+        let bound_program = program.get_program(&self.gpu)?;
+        let program_attributes = bound_program.attributes();
+
+        let transform_feedback = bound_program.transform_feedback().ok_or_else(|| {
+            Error::InvalidUsage(
+                "render_feedback requires a program created with_transform_feedback.".to_string(),
+            )
+        })?;
+
+        let expected_outputs = match transform_feedback.mode {
+            FeedbackBufferMode::Interleaved => 1,
+            FeedbackBufferMode::Separate => transform_feedback.varyings.len(),
+        };
+
+        if outputs.len() != expected_outputs {
+            return Err(Error::InvalidUsage(format!(
+                "Expected {} transform feedback output buffers, but got {}.",
+                expected_outputs,
+                outputs.len()
+            )));
+        }
+
+        let output_names: Vec<String> = outputs
+            .iter()
+            .flat_map(|o| o.describe())
+            .map(|d| d.variable_name)
+            .collect();
+        if output_names != transform_feedback.varyings {
+            log::warn!(
+                "Transform feedback captures varyings {:?}, but the output buffers describe {:?}.",
+                transform_feedback.varyings,
+                output_names
+            );
+        }
+
+        let mut bg = BufferBindingGroup::new(program_attributes.clone());
+        bg.add_buffer(vertex_buffer, 0);
+
+        let count = vertex_buffer.len();
+        let outputs = outputs.iter().map(|o| o.feedback_handle(count)).collect();
+
+        self.render_impl(
+            DrawCall::TransformFeedback {
+                first: 0,
+                count,
+                outputs,
+            },
+            program,
+            VertexArray::Bindings(bg.bindings),
         )
     }
 }
@@ -1,7 +1,11 @@
+use crate::error::{Error, GlObject, Result};
 use crate::webgl::buffer::{BufferBindPoint, BufferUsageHint};
-use anyhow::{anyhow, Result};
 use bytemuck::Pod;
-use std::{cell::RefCell, hash::Hash, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    hash::Hash,
+    rc::Rc,
+};
 use web_sys::{WebGl2RenderingContext, WebGlBuffer};
 
 pub enum BindResult {
@@ -50,6 +54,14 @@ pub struct BufferHandleInner {
     gl_objects: RefCell<Option<BufferGlObjects>>,
     data: RefCell<DataWithMarker>,
     usage_hint: BufferUsageHint,
+
+    /// `ArrayBuffer` for vertex data or `ElementArrayBuffer` for indices.
+    /// WebGL doesn't allow a buffer to change between the two once bound.
+    bind_point: BufferBindPoint,
+
+    /// Incremented whenever the data is set, so that CPU-side structures
+    /// derived from the data can tell when to rebuild.
+    version: Cell<u64>,
 }
 
 #[derive(Clone)]
@@ -86,16 +98,24 @@ impl PartialEq for BufferHandle {
 impl Eq for BufferHandle {}
 
 impl BufferHandle {
-    fn new_impl(usage_hint: BufferUsageHint) -> BufferHandle {
+    fn new_impl(usage_hint: BufferUsageHint, bind_point: BufferBindPoint) -> BufferHandle {
         BufferHandle(Rc::new(BufferHandleInner {
             gl_objects: RefCell::new(None),
             data: RefCell::new(DataWithMarker::default()),
             usage_hint,
+            bind_point,
+            version: Cell::new(0),
         }))
     }
 
     pub fn new(usage_hint: BufferUsageHint) -> BufferHandle {
-        Self::new_impl(usage_hint)
+        Self::new_impl(usage_hint, BufferBindPoint::ArrayBuffer)
+    }
+
+    /// Create a buffer of vertex indices. Binding it attaches it to the
+    /// currently bound vertex array.
+    pub fn new_index(usage_hint: BufferUsageHint) -> BufferHandle {
+        Self::new_impl(usage_hint, BufferBindPoint::ElementArrayBuffer)
     }
 
     pub fn set_data<T: Pod>(&self, data: Vec<T>) {
@@ -104,27 +124,43 @@ impl BufferHandle {
             data: Box::new(data),
             dirty: true,
         };
+        self.0.version.set(self.0.version.get() + 1);
+    }
+
+    pub fn version(&self) -> u64 {
+        self.0.version.get()
     }
 
     pub fn len(&self) -> usize {
         self.0.data.borrow().length
     }
 
+    pub fn byte_len(&self) -> usize {
+        self.0.data.borrow().data.byte_len()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.0.data.borrow().length == 0
     }
 
+    /// Copy the data last set with `set_data` into `dst`, which must be
+    /// `byte_len()` bytes long.
+    pub fn copy_data(&self, dst: &mut [u8]) {
+        dst.copy_from_slice(self.0.data.borrow().data.as_bytes());
+    }
+
     fn create(
         gl: &WebGl2RenderingContext,
         data: &[u8],
         usage_hint: BufferUsageHint,
+        bind_point: BufferBindPoint,
     ) -> Result<BufferGlObjects> {
         let buffer = gl
             .create_buffer()
-            .ok_or_else(|| anyhow!("Couldn't create buffer."))?;
+            .ok_or(Error::ObjectCreation(GlObject::Buffer))?;
 
-        gl.bind_buffer(BufferBindPoint::ArrayBuffer as _, Some(&buffer));
-        gl.buffer_data_with_u8_array(BufferBindPoint::ArrayBuffer as _, data, usage_hint as _);
+        gl.bind_buffer(bind_point as _, Some(&buffer));
+        gl.buffer_data_with_u8_array(bind_point as _, data, usage_hint as _);
 
         Ok(BufferGlObjects {
             buffer,
@@ -132,6 +168,25 @@ impl BufferHandle {
         })
     }
 
+    /// Bind the buffer to an indexed binding point, such as a transform
+    /// feedback output, creating or uploading it first if necessary.
+    pub fn bind_base(
+        &self,
+        gl: &WebGl2RenderingContext,
+        bind_point: BufferBindPoint,
+        index: u32,
+    ) -> Result<()> {
+        self.bind(gl)?;
+
+        let gl_objects = self.0.gl_objects.borrow();
+        let gl_objects = gl_objects
+            .as_ref()
+            .ok_or(Error::ObjectCreation(GlObject::Buffer))?;
+        gl.bind_buffer_base(bind_point as _, index, Some(&gl_objects.buffer));
+
+        Ok(())
+    }
+
     pub fn bind(&self, gl: &WebGl2RenderingContext) -> Result<BindResult> {
         let inner = &self.0;
 
@@ -144,9 +199,9 @@ impl BufferHandle {
         if let Some(gl_objects) = &mut *gl_objects {
             if dirty {
                 if gl_objects.capacity >= data.data.byte_len() {
-                    gl.bind_buffer(BufferBindPoint::ArrayBuffer as _, Some(&gl_objects.buffer));
+                    gl.bind_buffer(inner.bind_point as _, Some(&gl_objects.buffer));
                     gl.buffer_sub_data_with_i32_and_u8_array(
-                        BufferBindPoint::ArrayBuffer as _,
+                        inner.bind_point as _,
                         0,
                         data.data.as_bytes(),
                     );
@@ -160,11 +215,12 @@ impl BufferHandle {
                     );
                     gl.delete_buffer(Some(&gl_objects.buffer));
 
-                    *gl_objects = Self::create(gl, data.data.as_bytes(), inner.usage_hint)?;
+                    *gl_objects =
+                        Self::create(gl, data.data.as_bytes(), inner.usage_hint, inner.bind_point)?;
                     Ok(BindResult::BoundNew)
                 }
             } else {
-                gl.bind_buffer(BufferBindPoint::ArrayBuffer as _, Some(&gl_objects.buffer));
+                gl.bind_buffer(inner.bind_point as _, Some(&gl_objects.buffer));
                 Ok(BindResult::BoundExisting)
             }
         } else {
@@ -173,7 +229,12 @@ impl BufferHandle {
                 "Buffer used for the first time, creating with {} bytes.",
                 data.data.byte_len()
             );
-            *gl_objects = Some(Self::create(gl, data.data.as_bytes(), inner.usage_hint)?);
+            *gl_objects = Some(Self::create(
+                gl,
+                data.data.as_bytes(),
+                inner.usage_hint,
+                inner.bind_point,
+            )?);
 
             Ok(BindResult::BoundNew)
         }
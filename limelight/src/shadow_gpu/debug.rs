@@ -0,0 +1,105 @@
+use super::ProgramHandle;
+use crate::error::{Error, Result};
+use web_sys::WebGl2RenderingContext;
+
+/// Checks `getError` after GL calls when debug mode is enabled, attaching
+/// a description of the call and the program it was made for.
+///
+/// Checking for errors forces the browser to synchronize with the GPU
+/// process, so this is a no-op unless debug mode is on.
+#[derive(Clone, Copy)]
+pub struct ErrorCheck<'a> {
+    gl: &'a WebGl2RenderingContext,
+    program: Option<&'a ProgramHandle>,
+    enabled: bool,
+}
+
+impl<'a> ErrorCheck<'a> {
+    pub fn new(
+        gl: &'a WebGl2RenderingContext,
+        program: Option<&'a ProgramHandle>,
+        enabled: bool,
+    ) -> Self {
+        ErrorCheck {
+            gl,
+            program,
+            enabled,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Describe the program, using its label if it has one.
+    pub fn program_name(&self) -> String {
+        match self.program {
+            Some(ProgramHandle {
+                label: Some(label), ..
+            }) => format!("program '{}'", label),
+            Some(_) => "unlabeled program".to_string(),
+            None => "no program".to_string(),
+        }
+    }
+
+    /// The name of the program attribute at `location`, for error messages.
+    pub fn attribute_name(&self, location: u32) -> String {
+        self.program
+            .and_then(|program| {
+                program
+                    .attributes
+                    .iter()
+                    .find(|(_, info)| info.location == location as usize)
+                    .map(|(name, _)| format!("`{}`", name))
+            })
+            .unwrap_or_else(|| "<unknown>".to_string())
+    }
+
+    /// Return an error if a GL call since the last check failed. `action`
+    /// describes the call, and is only evaluated if there was an error.
+    pub fn after(&self, action: impl FnOnce() -> String) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let code = self.gl.get_error();
+        if code == WebGl2RenderingContext::NO_ERROR {
+            Ok(())
+        } else {
+            Err(Error::Gl {
+                code,
+                context: Some(format!("{}: {}", self.program_name(), action())),
+            })
+        }
+    }
+
+    /// In debug mode, return an error if any attribute of the program
+    /// isn't bound to a buffer. WebGL reads a constant value for unbound
+    /// attributes, which is rarely intended.
+    pub fn check_attributes(&self, bound_locations: &[u32]) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if let Some(program) = self.program {
+            for (name, info) in &program.attributes {
+                // Built-in inputs like gl_VertexID are reported as attributes
+                // on some platforms, but don't need a buffer.
+                if name.starts_with("gl_") {
+                    continue;
+                }
+
+                if !bound_locations.contains(&(info.location as u32)) {
+                    return Err(Error::InvalidUsage(format!(
+                        "{}: attribute `{}` location {} has no buffer.",
+                        self.program_name(),
+                        name,
+                        info.location
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use super::{renderbuffer::RenderbufferHandle, texture::TextureHandle, GpuBind};
+use crate::error::{Error, GlObject, Result};
+use crate::webgl::texture::PixelFormat;
+use std::{cell::RefCell, rc::Rc};
+use web_sys::{WebGl2RenderingContext, WebGlFramebuffer};
+
+/// The image a framebuffer renders into.
+#[derive(Clone, PartialEq)]
+pub enum ColorAttachment {
+    Texture(TextureHandle),
+
+    /// A multisampled renderbuffer, which has to be resolved into a texture
+    /// or the canvas before it can be read.
+    Multisampled(RenderbufferHandle),
+}
+
+impl ColorAttachment {
+    pub fn width(&self) -> i32 {
+        match self {
+            ColorAttachment::Texture(texture) => texture.width(),
+            ColorAttachment::Multisampled(renderbuffer) => renderbuffer.width(),
+        }
+    }
+
+    pub fn height(&self) -> i32 {
+        match self {
+            ColorAttachment::Texture(texture) => texture.height(),
+            ColorAttachment::Multisampled(renderbuffer) => renderbuffer.height(),
+        }
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        match self {
+            ColorAttachment::Texture(texture) => texture.format(),
+            ColorAttachment::Multisampled(renderbuffer) => renderbuffer.format(),
+        }
+    }
+
+    pub fn is_multisampled(&self) -> bool {
+        matches!(self, ColorAttachment::Multisampled(_))
+    }
+}
+
+struct FramebufferHandleInner {
+    framebuffer: RefCell<Option<WebGlFramebuffer>>,
+    color: ColorAttachment,
+}
+
+/// A framebuffer with a single color attachment. The GL framebuffer is
+/// created the first time it is bound.
+#[derive(Clone)]
+pub struct FramebufferHandle(Rc<FramebufferHandleInner>);
+
+impl PartialEq for FramebufferHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl FramebufferHandle {
+    pub fn new(color: ColorAttachment) -> Self {
+        FramebufferHandle(Rc::new(FramebufferHandleInner {
+            framebuffer: RefCell::new(None),
+            color,
+        }))
+    }
+
+    pub fn color(&self) -> &ColorAttachment {
+        &self.0.color
+    }
+
+    /// Bind to `target`, which is one of `FRAMEBUFFER`, `READ_FRAMEBUFFER`
+    /// or `DRAW_FRAMEBUFFER`.
+    pub fn bind(&self, gl: &WebGl2RenderingContext, target: u32) -> Result<()> {
+        let mut framebuffer = self.0.framebuffer.borrow_mut();
+
+        if let Some(framebuffer) = &*framebuffer {
+            gl.bind_framebuffer(target, Some(framebuffer));
+            return Ok(());
+        }
+
+        log::info!("Creating framebuffer.");
+        let new_framebuffer = gl
+            .create_framebuffer()
+            .ok_or(Error::ObjectCreation(GlObject::Framebuffer))?;
+
+        match &self.0.color {
+            ColorAttachment::Texture(texture) => {
+                let texture = texture.bind(gl)?;
+                gl.bind_framebuffer(target, Some(&new_framebuffer));
+                gl.framebuffer_texture_2d(
+                    target,
+                    WebGl2RenderingContext::COLOR_ATTACHMENT0,
+                    WebGl2RenderingContext::TEXTURE_2D,
+                    Some(&texture),
+                    0,
+                );
+            }
+            ColorAttachment::Multisampled(renderbuffer) => {
+                let renderbuffer = renderbuffer.bind(gl)?;
+                gl.bind_framebuffer(target, Some(&new_framebuffer));
+                gl.framebuffer_renderbuffer(
+                    target,
+                    WebGl2RenderingContext::COLOR_ATTACHMENT0,
+                    WebGl2RenderingContext::RENDERBUFFER,
+                    Some(&renderbuffer),
+                );
+            }
+        }
+
+        let status = gl.check_framebuffer_status(target);
+        if status != WebGl2RenderingContext::FRAMEBUFFER_COMPLETE {
+            return Err(Error::IncompleteFramebuffer { status });
+        }
+
+        *framebuffer = Some(new_framebuffer);
+        Ok(())
+    }
+}
+
+impl GpuBind for Option<FramebufferHandle> {
+    fn gpu_bind(&self, gl: &WebGl2RenderingContext) -> Result<()> {
+        if let Some(framebuffer) = self {
+            framebuffer.bind(gl, WebGl2RenderingContext::FRAMEBUFFER)?;
+        } else {
+            gl.bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+        }
+
+        Ok(())
+    }
+}
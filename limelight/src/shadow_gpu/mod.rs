@@ -1,19 +1,37 @@
 pub use self::buffer::BufferHandle;
+use self::debug::ErrorCheck;
+pub use self::framebuffer::{ColorAttachment, FramebufferHandle};
+pub use self::readback::PendingRead;
+pub use self::renderbuffer::RenderbufferHandle;
 pub use self::state::BufferBinding;
-use self::vao::VaoHandle;
-pub use self::{program::ProgramHandle, state::GpuState};
-use crate::webgl::buffer::BufferUsageHint;
-use crate::webgl::types::GlSizedDataType;
+pub use self::texture::TextureHandle;
+pub use self::timer::TimedSection;
+use self::timer::TimerQueries;
+pub use self::vao::VaoHandle;
+pub use self::{
+    program::{ProgramHandle, TransformFeedbackVaryings},
+    state::{GpuState, VertexArray},
+};
+use crate::error::{Error, GlObject, Result, ShaderMessage, ShaderStage};
+use crate::webgl::buffer::{BufferBindPoint, BufferUsageHint};
+use crate::webgl::texture::ReadFormat;
+use crate::webgl::types::{DataType, GlSizedDataType};
 use crate::DrawMode;
-use anyhow::{anyhow, Result};
 use std::collections::BTreeMap;
 use std::{collections::HashMap, rc::Rc};
 pub use uniforms::{UniformHandle, UniformValue, UniformValueType};
-use web_sys::{WebGl2RenderingContext, WebGlShader};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{HtmlCanvasElement, WebGl2RenderingContext, WebGlShader};
 
 mod buffer;
+mod debug;
+mod framebuffer;
 mod program;
+mod readback;
+mod renderbuffer;
 mod state;
+mod texture;
+mod timer;
 mod uniforms;
 mod vao;
 
@@ -34,6 +52,8 @@ pub struct ShadowGpu {
     gl: WebGl2RenderingContext,
     state: GpuState,
     vaos: HashMap<BTreeMap<BufferHandle, Vec<BufferBinding>>, VaoHandle>,
+    timer: Option<TimerQueries>,
+    debug: bool,
 }
 
 impl ShadowGpu {
@@ -42,6 +62,54 @@ impl ShadowGpu {
             gl,
             state: GpuState::default(),
             vaos: HashMap::default(),
+            timer: None,
+            debug: false,
+        }
+    }
+
+    /// In debug mode, `getError` is checked after every GL call made while
+    /// drawing, and failures are returned with a description of the call.
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+    }
+
+    pub fn is_debug(&self) -> bool {
+        self.debug
+    }
+
+    fn error_check<'a>(&'a self, state: &'a GpuState) -> ErrorCheck<'a> {
+        ErrorCheck::new(&self.gl, state.program.as_ref(), self.debug)
+    }
+
+    /// Enable GPU timer queries. Returns `false` (and leaves timed sections
+    /// as no-ops) if `EXT_disjoint_timer_query_webgl2` is not supported.
+    pub fn enable_timer_queries(&mut self) -> bool {
+        if self.timer.is_none() {
+            self.timer = TimerQueries::new(&self.gl);
+        }
+
+        self.timer.is_some()
+    }
+
+    pub fn begin_timed_section(&mut self, label: &str) -> Result<()> {
+        if let Some(timer) = &mut self.timer {
+            timer.begin(&self.gl, label)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn end_timed_section(&mut self) {
+        if let Some(timer) = &mut self.timer {
+            timer.end(&self.gl);
+        }
+    }
+
+    pub fn poll_timed_sections(&mut self) -> Vec<TimedSection> {
+        if let Some(timer) = &mut self.timer {
+            timer.poll(&self.gl)
+        } else {
+            Vec::new()
         }
     }
 
@@ -54,7 +122,8 @@ impl ShadowGpu {
     ) -> Result<()> {
         self.set_state(state)?;
         self.gl.draw_arrays(mode as _, first, count);
-        Ok(())
+        self.error_check(state)
+            .after(|| format!("drawArrays({:?}, first {}, count {})", mode, first, count))
     }
 
     pub fn draw_arrays_instanced(
@@ -68,6 +137,88 @@ impl ShadowGpu {
         self.set_state(state)?;
         self.gl
             .draw_arrays_instanced(mode as _, first, count, instance_count);
+        self.error_check(state).after(|| {
+            format!(
+                "drawArraysInstanced({:?}, first {}, count {}, instances {})",
+                mode, first, count, instance_count
+            )
+        })
+    }
+
+    /// Draw `count` vertices of `indices`, a buffer of `u32` indices into the
+    /// vertex arrays of `state`.
+    pub fn draw_elements(
+        &mut self,
+        state: &GpuState,
+        mode: DrawMode,
+        count: i32,
+        indices: &BufferHandle,
+    ) -> Result<()> {
+        self.set_state(state)?;
+        let check = self.error_check(state);
+
+        // The element array binding is part of the vertex array state, so
+        // this has to happen after the vertex array is bound.
+        indices.bind(&self.gl)?;
+        check.after(|| "binding the index buffer".to_string())?;
+
+        self.gl
+            .draw_elements_with_i32(mode as _, count, WebGl2RenderingContext::UNSIGNED_INT, 0);
+        check.after(|| format!("drawElements({:?}, count {})", mode, count))
+    }
+
+    /// Draw with transform feedback active, capturing the program's feedback
+    /// varyings into `outputs` (one buffer per binding index).
+    pub fn draw_arrays_feedback(
+        &mut self,
+        state: &GpuState,
+        mode: DrawMode,
+        first: i32,
+        count: i32,
+        outputs: &[BufferHandle],
+    ) -> Result<()> {
+        match mode {
+            DrawMode::Points | DrawMode::Lines | DrawMode::Triangles => (),
+            _ => {
+                return Err(Error::InvalidUsage(format!(
+                    "Transform feedback requires Points, Lines, or Triangles, not {:?}.",
+                    mode
+                )))
+            }
+        }
+
+        self.set_state(state)?;
+        let check = self.error_check(state);
+
+        for (index, output) in outputs.iter().enumerate() {
+            output.bind_base(
+                &self.gl,
+                BufferBindPoint::TransformFeedbackBuffer,
+                index as _,
+            )?;
+            check.after(|| format!("binding transform feedback output {}", index))?;
+        }
+
+        self.gl.begin_transform_feedback(mode as _);
+        self.gl.draw_arrays(mode as _, first, count);
+        self.gl.end_transform_feedback();
+        check.after(|| {
+            format!(
+                "drawArrays({:?}, first {}, count {}) with transform feedback",
+                mode, first, count
+            )
+        })?;
+
+        // A buffer can't be bound for transform feedback while it is used as
+        // a vertex attribute, so unbind the outputs right away.
+        for index in 0..outputs.len() {
+            self.gl.bind_buffer_base(
+                BufferBindPoint::TransformFeedbackBuffer as _,
+                index as _,
+                None,
+            );
+        }
+
         Ok(())
     }
 
@@ -75,48 +226,330 @@ impl ShadowGpu {
         let location = self
             .gl
             .get_uniform_location(&program.program, name)
-            .ok_or_else(|| anyhow!("Uniform {} not found.", name))?;
+            .ok_or_else(|| Error::UniformNotFound {
+                name: name.to_string(),
+            })?;
 
         Ok(UniformHandle::new(location))
     }
 
+    /// The names of the program's active uniforms.
+    pub fn active_uniform_names(&self, program: &ProgramHandle) -> Vec<String> {
+        let count = self
+            .gl
+            .get_program_parameter(&program.program, WebGl2RenderingContext::ACTIVE_UNIFORMS)
+            .as_f64()
+            .unwrap_or(0.) as u32;
+
+        (0..count)
+            .filter_map(|i| self.gl.get_active_uniform(&program.program, i))
+            .map(|info| info.name())
+            .collect()
+    }
+
+    fn set_framebuffer(&mut self, framebuffer: &Option<FramebufferHandle>) -> Result<()> {
+        if self.state.framebuffer != *framebuffer {
+            framebuffer.gpu_bind(&self.gl)?;
+            self.state.framebuffer = framebuffer.clone();
+            self.forget_textures();
+        }
+
+        Ok(())
+    }
+
+    /// Binding a framebuffer for the first time creates its texture, which
+    /// changes the binding of the active texture unit behind our back, so
+    /// the shadowed texture bindings are dropped whenever one is bound.
+    fn forget_textures(&mut self) {
+        self.state.textures.clear();
+    }
+
+    /// The canvas the context belongs to, if it is an HTML canvas.
+    pub fn canvas(&self) -> Option<HtmlCanvasElement> {
+        self.gl.canvas()?.dyn_into().ok()
+    }
+
+    /// The size in pixels of a framebuffer (or the canvas, if `None`).
+    pub fn framebuffer_size(&self, framebuffer: &Option<FramebufferHandle>) -> (i32, i32) {
+        if let Some(framebuffer) = framebuffer {
+            (framebuffer.color().width(), framebuffer.color().height())
+        } else {
+            (
+                self.gl.drawing_buffer_width(),
+                self.gl.drawing_buffer_height(),
+            )
+        }
+    }
+
+    /// Clear the color of a normalized framebuffer (or the canvas, if `None`).
+    pub fn clear(
+        &mut self,
+        framebuffer: &Option<FramebufferHandle>,
+        color: [f32; 4],
+    ) -> Result<()> {
+        self.set_framebuffer(framebuffer)?;
+        self.gl.clear_color(color[0], color[1], color[2], color[3]);
+        self.gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
+
+        Ok(())
+    }
+
+    /// Clear the color of an unsigned integer framebuffer.
+    pub fn clear_integer(
+        &mut self,
+        framebuffer: &Option<FramebufferHandle>,
+        values: [u32; 4],
+    ) -> Result<()> {
+        self.set_framebuffer(framebuffer)?;
+        self.gl
+            .clear_bufferuiv_with_u32_array(WebGl2RenderingContext::COLOR, 0, &values);
+
+        Ok(())
+    }
+
+    /// Copy the color of a framebuffer into another framebuffer (or the
+    /// canvas, if `None`), resolving it if it is multisampled. Both must be
+    /// the same size, since multisampled framebuffers can't be scaled.
+    pub fn blit_framebuffer(
+        &mut self,
+        source: &FramebufferHandle,
+        destination: &Option<FramebufferHandle>,
+    ) -> Result<()> {
+        let (width, height) = (source.color().width(), source.color().height());
+        if self.framebuffer_size(destination) != (width, height) {
+            return Err(Error::InvalidUsage(format!(
+                "Can't resolve a {}x{} render target into a {}x{} one.",
+                width,
+                height,
+                self.framebuffer_size(destination).0,
+                self.framebuffer_size(destination).1,
+            )));
+        }
+
+        if let Some(destination) = destination {
+            if destination.color().is_multisampled() {
+                return Err(Error::InvalidUsage(
+                    "Can't resolve into a multisampled render target.".to_string(),
+                ));
+            }
+
+            if destination.color().format().is_integer() != source.color().format().is_integer() {
+                return Err(Error::InvalidUsage(
+                    "Can't resolve between integer and normalized render targets.".to_string(),
+                ));
+            }
+        }
+
+        source.bind(&self.gl, WebGl2RenderingContext::READ_FRAMEBUFFER)?;
+        self.forget_textures();
+        if let Some(destination) = destination {
+            destination.bind(&self.gl, WebGl2RenderingContext::DRAW_FRAMEBUFFER)?;
+        } else {
+            self.gl
+                .bind_framebuffer(WebGl2RenderingContext::DRAW_FRAMEBUFFER, None);
+        }
+
+        self.gl.blit_framebuffer(
+            0,
+            0,
+            width,
+            height,
+            0,
+            0,
+            width,
+            height,
+            WebGl2RenderingContext::COLOR_BUFFER_BIT,
+            WebGl2RenderingContext::NEAREST,
+        );
+
+        // Blitting changes the read and draw bindings independently, so
+        // restore the framebuffer the shadow state expects.
+        if let Some(framebuffer) = &self.state.framebuffer {
+            framebuffer.bind(&self.gl, WebGl2RenderingContext::FRAMEBUFFER)?;
+        } else {
+            self.gl
+                .bind_framebuffer(WebGl2RenderingContext::FRAMEBUFFER, None);
+        }
+
+        Ok(())
+    }
+
+    /// Read a rectangle of pixels from a framebuffer (or the canvas, if `None`).
+    /// Rows are returned bottom-to-top, as in GL.
+    pub fn read_pixels(
+        &mut self,
+        framebuffer: &Option<FramebufferHandle>,
+        (x, y, width, height): (i32, i32, i32, i32),
+        format: ReadFormat,
+    ) -> Result<Vec<u8>> {
+        if let Some(framebuffer) = framebuffer {
+            if framebuffer.color().is_multisampled() {
+                return Err(Error::InvalidUsage(
+                    "Can't read from a multisampled render target; resolve it first.".to_string(),
+                ));
+            }
+        }
+
+        self.set_framebuffer(framebuffer)?;
+
+        let mut data = vec![0u8; (width * height) as usize * format.bytes_per_pixel()];
+        match format.data_type() {
+            DataType::UnsignedByte => self.gl.read_pixels_with_opt_u8_array(
+                x,
+                y,
+                width,
+                height,
+                format.format(),
+                format.data_type() as _,
+                Some(&mut data),
+            ),
+            _ => {
+                // WebGL requires the destination array type to match the
+                // pixel type, so read into a Uint32Array and copy its bytes.
+                let array = js_sys::Uint32Array::new_with_length((data.len() / 4) as _);
+                let result = self.gl.read_pixels_with_opt_array_buffer_view(
+                    x,
+                    y,
+                    width,
+                    height,
+                    format.format(),
+                    format.data_type() as _,
+                    Some(&array),
+                );
+                js_sys::Uint8Array::new(&array.buffer()).copy_to(&mut data);
+                result
+            }
+        }
+        .map_err(Error::from_js)?;
+
+        Ok(data)
+    }
+
+    /// Read a buffer's contents into `dst`, stalling until the GPU is done
+    /// writing to it.
+    pub fn read_buffer(&self, buffer: &BufferHandle, dst: &mut [u8]) -> Result<()> {
+        buffer.bind(&self.gl)?;
+        self.gl.get_buffer_sub_data_with_i32_and_u8_array(
+            BufferBindPoint::ArrayBuffer as _,
+            0,
+            dst,
+        );
+
+        Ok(())
+    }
+
+    /// Queue a read of a buffer's contents, to be collected with
+    /// `finish_read` once `is_read_ready` returns `true`.
+    pub fn read_buffer_async(&self, buffer: &BufferHandle) -> Result<PendingRead> {
+        PendingRead::new(&self.gl, buffer)
+    }
+
+    pub fn is_read_ready(&self, pending: &PendingRead) -> Result<bool> {
+        pending.is_ready(&self.gl)
+    }
+
+    pub fn finish_read(&self, pending: PendingRead, dst: &mut [u8]) {
+        pending.finish(&self.gl, dst)
+    }
+
     fn set_state(&mut self, new_state: &GpuState) -> Result<()> {
+        let check = ErrorCheck::new(&self.gl, new_state.program.as_ref(), self.debug);
+
+        // Errors raised before this draw would otherwise be attributed to it.
+        check.after(|| "a GL call made before this draw".to_string())?;
+
+        // Framebuffer
+        if self.state.framebuffer != new_state.framebuffer {
+            new_state.framebuffer.gpu_bind(&self.gl)?;
+            self.state.framebuffer = new_state.framebuffer.clone();
+            self.state.textures.clear();
+            check.after(|| "binding the render target".to_string())?;
+        }
+
+        // Viewport
+        if self.state.viewport != new_state.viewport {
+            let (x, y, width, height) = new_state.viewport;
+            self.gl.viewport(x, y, width, height);
+            self.state.viewport = new_state.viewport;
+            check.after(|| format!("setting viewport to {:?}", new_state.viewport))?;
+        }
+
         // Program
         if self.state.program != new_state.program {
             new_state.program.gpu_bind(&self.gl)?;
             self.state.program = new_state.program.clone();
+            check.after(|| "binding the program".to_string())?;
         }
 
         // Globals
         if self.state.globals.blend_func != new_state.globals.blend_func {
             new_state.globals.blend_func.gpu_bind(&self.gl)?;
             self.state.globals.blend_func = new_state.globals.blend_func.clone();
+            check.after(|| format!("setting blend function {:?}", new_state.globals.blend_func))?;
         }
 
-        let vao = if let Some(vao) = self.vaos.get_mut(&new_state.buffers) {
-            vao
-        } else {
-            // Create VAO with bindings.
-            let vao = VaoHandle {
-                buffers: new_state.buffers.clone(),
-                vao: None,
-            };
-            self.vaos.insert(new_state.buffers.clone(), vao);
-            self.vaos.get_mut(&new_state.buffers).unwrap()
-        };
+        if self.state.globals.rasterizer_discard != new_state.globals.rasterizer_discard {
+            if new_state.globals.rasterizer_discard {
+                self.gl.enable(WebGl2RenderingContext::RASTERIZER_DISCARD);
+            } else {
+                self.gl.disable(WebGl2RenderingContext::RASTERIZER_DISCARD);
+            }
+            self.state.globals.rasterizer_discard = new_state.globals.rasterizer_discard;
+            check.after(|| "toggling rasterizer discard".to_string())?;
+        }
 
-        vao.gpu_bind(&self.gl)?;
+        match &new_state.vertex_array {
+            VertexArray::Bindings(buffers) => {
+                if check.enabled() {
+                    check.check_attributes(&bound_locations(buffers.values()))?;
+                }
 
-        // Uniforms
-        for (location, value) in &new_state.uniforms {
-            if let Some(v) = self.state.uniforms.get(location) {
-                if v == value {
-                    continue;
+                let vao = if let Some(vao) = self.vaos.get_mut(buffers) {
+                    vao
+                } else {
+                    // Create VAO with bindings.
+                    let vao = VaoHandle {
+                        buffers: buffers.clone(),
+                        vao: None,
+                    };
+                    self.vaos.insert(buffers.clone(), vao);
+                    self.vaos.get_mut(buffers).unwrap()
+                };
+
+                vao.gpu_bind(&self.gl, check)?;
+            }
+            VertexArray::Owned(vao) => {
+                let mut vao = vao.borrow_mut();
+                if check.enabled() {
+                    check.check_attributes(&bound_locations(vao.buffers.values()))?;
                 }
+
+                vao.gpu_bind(&self.gl, check)?;
             }
+        }
+
+        // Textures
+        for (unit, texture) in new_state.textures.iter().enumerate() {
+            if self.state.textures.get(unit) == Some(texture) && !texture.has_pending_uploads() {
+                continue;
+            }
+
+            self.gl
+                .active_texture(WebGl2RenderingContext::TEXTURE0 + unit as u32);
+            texture.bind(&self.gl)?;
+            check.after(|| format!("binding texture unit {}", unit))?;
 
-            self.state.uniforms.insert(location.clone(), *value);
+            if unit < self.state.textures.len() {
+                self.state.textures[unit] = texture.clone();
+            } else {
+                self.state.textures.push(texture.clone());
+            }
+        }
+
+        // Uniforms
+        for (location, value) in &new_state.uniforms {
             value.bind(&self.gl, location);
+            check.after(|| format!("setting uniform to {:?}", value))?;
         }
 
         Ok(())
@@ -130,14 +563,29 @@ impl ShadowGpu {
         &self,
         frag_shader: &FragmentShader,
         vertex_shader: &VertexShader,
+        transform_feedback: Option<&TransformFeedbackVaryings>,
     ) -> Result<ProgramHandle> {
         let gl_program = self
             .gl
             .create_program()
-            .ok_or_else(|| anyhow!("Error creating program."))?;
+            .ok_or(Error::ObjectCreation(GlObject::Program))?;
 
         self.gl.attach_shader(&gl_program, &frag_shader.0);
         self.gl.attach_shader(&gl_program, &vertex_shader.0);
+
+        if let Some(transform_feedback) = transform_feedback {
+            let varyings: js_sys::Array = transform_feedback
+                .varyings
+                .iter()
+                .map(|v| JsValue::from_str(v))
+                .collect();
+            self.gl.transform_feedback_varyings(
+                &gl_program,
+                &varyings,
+                transform_feedback.mode as _,
+            );
+        }
+
         self.gl.link_program(&gl_program);
 
         let active_attributes = self
@@ -168,16 +616,22 @@ impl ShadowGpu {
             .gl
             .get_program_parameter(&gl_program, WebGl2RenderingContext::LINK_STATUS)
         {
-            if let Some(info) = self.gl.get_program_info_log(&gl_program) {
-                return Err(anyhow!("Encountered error linking program: {}", info));
-            } else {
-                return Err(anyhow!("Encountered unknown error linking program."));
+            if self.gl.is_context_lost() {
+                return Err(Error::ContextLost);
             }
+
+            return Err(Error::ProgramLink {
+                log: self
+                    .gl
+                    .get_program_info_log(&gl_program)
+                    .unwrap_or_default(),
+            });
         }
 
         Ok(ProgramHandle {
             program: Rc::new(gl_program),
             attributes,
+            label: None,
         })
     }
 
@@ -185,7 +639,7 @@ impl ShadowGpu {
         let shader = self
             .gl
             .create_shader(shader_type as _)
-            .ok_or_else(|| anyhow!("Could not create shader."))?;
+            .ok_or(Error::ObjectCreation(GlObject::Shader))?;
         self.gl.shader_source(&shader, source);
         self.gl.compile_shader(&shader);
 
@@ -196,12 +650,20 @@ impl ShadowGpu {
             .unwrap_or(false)
         {
             Ok(shader)
+        } else if self.gl.is_context_lost() {
+            Err(Error::ContextLost)
         } else {
-            Err(self
-                .gl
-                .get_shader_info_log(&shader)
-                .map(|d| anyhow!("Error compiling shader {}", d))
-                .unwrap_or_else(|| anyhow!("Unknown error compiling shader.")))
+            let log = self.gl.get_shader_info_log(&shader).unwrap_or_default();
+
+            Err(Error::ShaderCompile {
+                stage: match shader_type {
+                    ShaderType::FragmentShader => ShaderStage::Fragment,
+                    ShaderType::VertexShader => ShaderStage::Vertex,
+                },
+                source: source.to_string(),
+                messages: ShaderMessage::parse_log(&log),
+                log,
+            })
         }
     }
 
@@ -220,13 +682,29 @@ impl ShadowGpu {
     pub fn get_error(&self) -> Result<()> {
         let error = self.gl.get_error();
         if error != WebGl2RenderingContext::NO_ERROR {
-            Err(anyhow!("WebGL Error: {:?}", error))
+            Err(Error::Gl {
+                code: error,
+                context: None,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns `Error::ContextLost` if the WebGL context has been lost.
+    pub fn check_context(&self) -> Result<()> {
+        if self.gl.is_context_lost() {
+            Err(Error::ContextLost)
         } else {
             Ok(())
         }
     }
 }
 
+fn bound_locations<'a>(bindings: impl Iterator<Item = &'a Vec<BufferBinding>>) -> Vec<u32> {
+    bindings.flatten().map(|binding| binding.location).collect()
+}
+
 #[derive(Copy, Clone)]
 #[repr(u32)]
 enum ShaderType {
@@ -1,5 +1,6 @@
 use super::{AttributeInfo, GpuBind};
-use anyhow::Result;
+use crate::error::Result;
+use crate::webgl::buffer::FeedbackBufferMode;
 use std::borrow::Borrow;
 use std::{collections::HashMap, rc::Rc};
 use web_sys::{WebGl2RenderingContext, WebGlProgram};
@@ -10,6 +11,9 @@ pub struct ProgramHandle {
 
     /// A map from attribute name to attribute location in the program.
     pub attributes: HashMap<String, AttributeInfo>,
+
+    /// A label used to identify the program in debug mode errors.
+    pub label: Option<String>,
 }
 
 impl GpuBind for Option<ProgramHandle> {
@@ -29,3 +33,11 @@ impl PartialEq for ProgramHandle {
         Rc::ptr_eq(&self.program, &other.program)
     }
 }
+
+/// Varyings to capture with transform feedback, as passed to
+/// `transformFeedbackVaryings` before the program is linked.
+#[derive(Clone, Debug)]
+pub struct TransformFeedbackVaryings {
+    pub varyings: Vec<String>,
+    pub mode: FeedbackBufferMode,
+}
@@ -0,0 +1,87 @@
+use super::BufferHandle;
+use crate::error::{Error, GlObject, Result};
+use crate::webgl::buffer::BufferBindPoint;
+use web_sys::{WebGl2RenderingContext, WebGlBuffer, WebGlSync};
+
+/// A buffer read that has been queued on the GPU but may not have completed.
+///
+/// The buffer's contents are copied into a staging buffer when the read is
+/// issued, and a fence is inserted after the copy. Once the fence has been
+/// signaled, the staging buffer can be read without stalling.
+pub struct PendingRead {
+    staging: WebGlBuffer,
+    sync: WebGlSync,
+    byte_len: usize,
+}
+
+impl PendingRead {
+    pub fn new(gl: &WebGl2RenderingContext, buffer: &BufferHandle) -> Result<Self> {
+        let byte_len = buffer.byte_len();
+        buffer.bind(gl)?;
+
+        let staging = gl
+            .create_buffer()
+            .ok_or(Error::ObjectCreation(GlObject::Buffer))?;
+        gl.bind_buffer(WebGl2RenderingContext::COPY_WRITE_BUFFER, Some(&staging));
+        gl.buffer_data_with_i32(
+            WebGl2RenderingContext::COPY_WRITE_BUFFER,
+            byte_len as _,
+            WebGl2RenderingContext::STREAM_READ,
+        );
+        gl.copy_buffer_sub_data_with_i32_and_i32_and_i32(
+            BufferBindPoint::ArrayBuffer as _,
+            WebGl2RenderingContext::COPY_WRITE_BUFFER,
+            0,
+            0,
+            byte_len as _,
+        );
+        gl.bind_buffer(WebGl2RenderingContext::COPY_WRITE_BUFFER, None);
+
+        let sync = gl
+            .fence_sync(WebGl2RenderingContext::SYNC_GPU_COMMANDS_COMPLETE, 0)
+            .ok_or(Error::ObjectCreation(GlObject::Sync))?;
+
+        // Make sure the fence actually reaches the GPU, otherwise it may
+        // never be signaled.
+        gl.flush();
+
+        Ok(PendingRead {
+            staging,
+            sync,
+            byte_len,
+        })
+    }
+
+    pub fn byte_len(&self) -> usize {
+        self.byte_len
+    }
+
+    /// Returns `false` without blocking if the GPU has not finished the copy.
+    pub fn is_ready(&self, gl: &WebGl2RenderingContext) -> Result<bool> {
+        match gl.client_wait_sync_with_u32(&self.sync, 0, 0) {
+            WebGl2RenderingContext::TIMEOUT_EXPIRED => Ok(false),
+            WebGl2RenderingContext::WAIT_FAILED => Err(Error::InvalidUsage(
+                "Waiting on fence sync failed.".to_string(),
+            )),
+            _ => Ok(true),
+        }
+    }
+
+    /// Copy the staged data into `dst` and release the GL objects. Should
+    /// only be called once `is_ready` has returned `true`.
+    pub fn finish(self, gl: &WebGl2RenderingContext, dst: &mut [u8]) {
+        gl.bind_buffer(
+            WebGl2RenderingContext::COPY_READ_BUFFER,
+            Some(&self.staging),
+        );
+        gl.get_buffer_sub_data_with_i32_and_u8_array(
+            WebGl2RenderingContext::COPY_READ_BUFFER,
+            0,
+            dst,
+        );
+        gl.bind_buffer(WebGl2RenderingContext::COPY_READ_BUFFER, None);
+
+        gl.delete_sync(Some(&self.sync));
+        gl.delete_buffer(Some(&self.staging));
+    }
+}
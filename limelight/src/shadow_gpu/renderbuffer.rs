@@ -0,0 +1,104 @@
+use crate::error::{Error, GlObject, Result};
+use crate::webgl::texture::PixelFormat;
+use std::{cell::RefCell, rc::Rc};
+use web_sys::{WebGl2RenderingContext, WebGlRenderbuffer};
+
+struct RenderbufferHandleInner {
+    renderbuffer: RefCell<Option<WebGlRenderbuffer>>,
+    width: i32,
+    height: i32,
+    format: PixelFormat,
+    samples: i32,
+}
+
+/// A multisampled renderbuffer. The GL renderbuffer is created the first
+/// time it is used.
+#[derive(Clone)]
+pub struct RenderbufferHandle(Rc<RenderbufferHandleInner>);
+
+impl PartialEq for RenderbufferHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl RenderbufferHandle {
+    pub fn new(width: i32, height: i32, format: PixelFormat, samples: i32) -> Self {
+        RenderbufferHandle(Rc::new(RenderbufferHandleInner {
+            renderbuffer: RefCell::new(None),
+            width,
+            height,
+            format,
+            samples,
+        }))
+    }
+
+    pub fn width(&self) -> i32 {
+        self.0.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.0.height
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.0.format
+    }
+
+    /// The requested sample count. The actual count is clamped to
+    /// `MAX_SAMPLES` when the renderbuffer is created.
+    pub fn samples(&self) -> i32 {
+        self.0.samples
+    }
+
+    /// Return the GL renderbuffer, creating it if necessary.
+    pub fn bind(&self, gl: &WebGl2RenderingContext) -> Result<WebGlRenderbuffer> {
+        let mut renderbuffer = self.0.renderbuffer.borrow_mut();
+
+        if let Some(renderbuffer) = &*renderbuffer {
+            return Ok(renderbuffer.clone());
+        }
+
+        let max_samples = gl
+            .get_parameter(WebGl2RenderingContext::MAX_SAMPLES)
+            .map_err(Error::from_js)?
+            .as_f64()
+            .unwrap_or(0.) as i32;
+        let samples = self.0.samples.min(max_samples);
+
+        if samples < self.0.samples {
+            log::warn!(
+                "Requested {} samples, but MAX_SAMPLES is {}; using {}.",
+                self.0.samples,
+                max_samples,
+                samples
+            );
+        }
+
+        log::info!(
+            "Creating {}x{} renderbuffer with format {:?} and {} samples.",
+            self.0.width,
+            self.0.height,
+            self.0.format,
+            samples
+        );
+        let new_renderbuffer = gl
+            .create_renderbuffer()
+            .ok_or(Error::ObjectCreation(GlObject::Renderbuffer))?;
+
+        gl.bind_renderbuffer(
+            WebGl2RenderingContext::RENDERBUFFER,
+            Some(&new_renderbuffer),
+        );
+        gl.renderbuffer_storage_multisample(
+            WebGl2RenderingContext::RENDERBUFFER,
+            samples,
+            self.0.format.internal_format(),
+            self.0.width,
+            self.0.height,
+        );
+
+        *renderbuffer = Some(new_renderbuffer.clone());
+        Ok(new_renderbuffer)
+    }
+}
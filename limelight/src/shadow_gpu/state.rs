@@ -1,7 +1,10 @@
 use crate::{state::StateDescriptor, webgl::types::SizedDataType};
-use std::collections::{BTreeMap, HashMap};
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
 
-use super::{program::ProgramHandle, BufferHandle, UniformHandle, UniformValue};
+use super::{
+    framebuffer::FramebufferHandle, program::ProgramHandle, BufferHandle, TextureHandle,
+    UniformHandle, UniformValue, VaoHandle,
+};
 
 #[derive(Hash, PartialEq, Eq, Clone, Debug)]
 pub struct BufferBinding {
@@ -15,10 +18,34 @@ pub struct BufferBinding {
     //pub buffer: BufferHandle,
 }
 
+/// The source of the vertex attribute bindings for a draw.
+pub enum VertexArray {
+    /// Bindings built for a single draw. VAOs for these are cached by the
+    /// `ShadowGpu`, keyed by the bindings.
+    Bindings(BTreeMap<BufferHandle, Vec<BufferBinding>>),
+
+    /// A VAO owned by a `VertexBinding`, which skips the cache lookup.
+    Owned(Rc<RefCell<VaoHandle>>),
+}
+
+impl Default for VertexArray {
+    fn default() -> Self {
+        VertexArray::Bindings(BTreeMap::new())
+    }
+}
+
 #[derive(Default)]
 pub struct GpuState {
     pub program: Option<ProgramHandle>,
-    pub buffers: BTreeMap<BufferHandle, Vec<BufferBinding>>,
-    pub uniforms: HashMap<UniformHandle, UniformValue>,
+    pub vertex_array: VertexArray,
+    /// Uniform values to upload to the program before drawing. Uniform
+    /// values persist per program, so callers only include changed values.
+    pub uniforms: Vec<(UniformHandle, UniformValue)>,
+    /// Textures to bind, indexed by texture unit.
+    pub textures: Vec<TextureHandle>,
     pub globals: StateDescriptor,
+    pub framebuffer: Option<FramebufferHandle>,
+
+    /// The viewport as `(x, y, width, height)` in pixels of the framebuffer.
+    pub viewport: (i32, i32, i32, i32),
 }
@@ -0,0 +1,230 @@
+use crate::error::{Error, GlObject, Result};
+use crate::webgl::texture::{PixelFormat, TextureKind};
+use crate::webgl::types::DataType;
+use std::{cell::RefCell, rc::Rc};
+use web_sys::{WebGl2RenderingContext, WebGlTexture};
+
+/// Pixel data waiting to be copied into a range of layers of a texture.
+struct TextureUpload {
+    first_layer: i32,
+    layers: i32,
+    data: Vec<u8>,
+}
+
+struct TextureHandleInner {
+    texture: RefCell<Option<WebGlTexture>>,
+    uploads: RefCell<Vec<TextureUpload>>,
+    kind: TextureKind,
+    width: i32,
+    height: i32,
+    depth: i32,
+    format: PixelFormat,
+}
+
+/// A texture with immutable storage. The GL texture is created, and pending
+/// uploads are copied into it, the next time it is bound.
+#[derive(Clone)]
+pub struct TextureHandle(Rc<TextureHandleInner>);
+
+impl PartialEq for TextureHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl TextureHandle {
+    pub fn new(width: i32, height: i32, format: PixelFormat) -> Self {
+        Self::new_layered(TextureKind::Texture2D, width, height, 1, format)
+    }
+
+    /// Create a texture array or 3D texture with `depth` layers.
+    pub fn new_layered(
+        kind: TextureKind,
+        width: i32,
+        height: i32,
+        depth: i32,
+        format: PixelFormat,
+    ) -> Self {
+        TextureHandle(Rc::new(TextureHandleInner {
+            texture: RefCell::new(None),
+            uploads: RefCell::new(Vec::new()),
+            kind,
+            width,
+            height,
+            depth,
+            format,
+        }))
+    }
+
+    pub fn kind(&self) -> TextureKind {
+        self.0.kind
+    }
+
+    pub fn width(&self) -> i32 {
+        self.0.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.0.height
+    }
+
+    /// The number of layers (or depth slices, for 3D textures). This is 1
+    /// for 2D textures.
+    pub fn depth(&self) -> i32 {
+        self.0.depth
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.0.format
+    }
+
+    /// Queue `data` to be copied into `layers` consecutive layers, starting
+    /// at `first_layer`. The caller is responsible for checking the range
+    /// and the length of `data`.
+    pub fn upload(&self, first_layer: i32, layers: i32, data: Vec<u8>) {
+        self.0.uploads.borrow_mut().push(TextureUpload {
+            first_layer,
+            layers,
+            data,
+        });
+    }
+
+    pub fn has_pending_uploads(&self) -> bool {
+        !self.0.uploads.borrow().is_empty()
+    }
+
+    /// Return the GL texture, creating it and copying pending uploads into
+    /// it if necessary. The texture is left bound to its target on the
+    /// active texture unit.
+    pub fn bind(&self, gl: &WebGl2RenderingContext) -> Result<WebGlTexture> {
+        let target = self.0.kind.target();
+        let mut texture = self.0.texture.borrow_mut();
+
+        let texture = if let Some(texture) = &*texture {
+            gl.bind_texture(target, Some(texture));
+            texture.clone()
+        } else {
+            let new_texture = self.create(gl)?;
+            *texture = Some(new_texture.clone());
+            new_texture
+        };
+
+        for upload in self.0.uploads.borrow_mut().drain(..) {
+            self.copy_upload(gl, &upload)?;
+        }
+
+        Ok(texture)
+    }
+
+    fn create(&self, gl: &WebGl2RenderingContext) -> Result<WebGlTexture> {
+        let target = self.0.kind.target();
+
+        log::info!(
+            "Creating {}x{}x{} {:?} with format {:?}.",
+            self.0.width,
+            self.0.height,
+            self.0.depth,
+            self.0.kind,
+            self.0.format
+        );
+        let new_texture = gl
+            .create_texture()
+            .ok_or(Error::ObjectCreation(GlObject::Texture))?;
+
+        gl.bind_texture(target, Some(&new_texture));
+        match self.0.kind {
+            TextureKind::Texture2D => gl.tex_storage_2d(
+                target,
+                1,
+                self.0.format.internal_format(),
+                self.0.width,
+                self.0.height,
+            ),
+            TextureKind::Texture2DArray | TextureKind::Texture3D => gl.tex_storage_3d(
+                target,
+                1,
+                self.0.format.internal_format(),
+                self.0.width,
+                self.0.height,
+                self.0.depth,
+            ),
+        }
+
+        // Integer textures can't be filtered.
+        let filter = if self.0.format.is_integer() {
+            WebGl2RenderingContext::NEAREST
+        } else {
+            WebGl2RenderingContext::LINEAR
+        };
+        gl.tex_parameteri(
+            target,
+            WebGl2RenderingContext::TEXTURE_MIN_FILTER,
+            filter as _,
+        );
+        gl.tex_parameteri(
+            target,
+            WebGl2RenderingContext::TEXTURE_MAG_FILTER,
+            filter as _,
+        );
+        gl.tex_parameteri(
+            target,
+            WebGl2RenderingContext::TEXTURE_WRAP_S,
+            WebGl2RenderingContext::CLAMP_TO_EDGE as _,
+        );
+        gl.tex_parameteri(
+            target,
+            WebGl2RenderingContext::TEXTURE_WRAP_T,
+            WebGl2RenderingContext::CLAMP_TO_EDGE as _,
+        );
+        if self.0.kind == TextureKind::Texture3D {
+            gl.tex_parameteri(
+                target,
+                WebGl2RenderingContext::TEXTURE_WRAP_R,
+                WebGl2RenderingContext::CLAMP_TO_EDGE as _,
+            );
+        }
+
+        Ok(new_texture)
+    }
+
+    fn copy_upload(&self, gl: &WebGl2RenderingContext, upload: &TextureUpload) -> Result<()> {
+        let format = self.0.format;
+
+        // WebGL requires the source array type to match the pixel type.
+        let bytes = js_sys::Uint8Array::from(upload.data.as_slice());
+        let view: js_sys::Object = match format.data_type() {
+            DataType::UnsignedByte => bytes.into(),
+            _ => js_sys::Uint32Array::new(&bytes.buffer()).into(),
+        };
+
+        match self.0.kind {
+            TextureKind::Texture2D => gl
+                .tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_array_buffer_view(
+                    WebGl2RenderingContext::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    self.0.width,
+                    self.0.height,
+                    format.format(),
+                    format.data_type() as _,
+                    Some(&view),
+                ),
+            TextureKind::Texture2DArray | TextureKind::Texture3D => gl
+                .tex_sub_image_3d_with_opt_array_buffer_view(
+                    self.0.kind.target(),
+                    0,
+                    0,
+                    0,
+                    upload.first_layer,
+                    self.0.width,
+                    self.0.height,
+                    upload.layers,
+                    format.format(),
+                    format.data_type() as _,
+                    Some(&view),
+                ),
+        }
+        .map_err(Error::from_js)
+    }
+}
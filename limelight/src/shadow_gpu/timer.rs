@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+
+use crate::error::{Error, GlObject, Result};
+use web_sys::{WebGl2RenderingContext, WebGlQuery};
+
+/// `TIME_ELAPSED_EXT` from `EXT_disjoint_timer_query_webgl2`.
+const TIME_ELAPSED_EXT: u32 = 0x88BF;
+
+/// `GPU_DISJOINT_EXT` from `EXT_disjoint_timer_query_webgl2`.
+const GPU_DISJOINT_EXT: u32 = 0x8FBB;
+
+/// The GPU time spent between a `begin_timed_section` / `end_timed_section` pair.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimedSection {
+    pub label: String,
+    pub nanoseconds: u64,
+}
+
+impl TimedSection {
+    pub fn milliseconds(&self) -> f64 {
+        self.nanoseconds as f64 / 1_000_000.
+    }
+}
+
+struct PendingQuery {
+    label: String,
+    query: WebGlQuery,
+}
+
+/// Tracks timer queries issued through `EXT_disjoint_timer_query_webgl2`.
+///
+/// Query results only become available a few frames after they are issued,
+/// so completed queries are kept in a queue until `poll` finds their results.
+/// WebGL only allows one `TIME_ELAPSED_EXT` query to be active at a time,
+/// so sections can't be nested.
+#[derive(Default)]
+pub struct TimerQueries {
+    active: Option<PendingQuery>,
+    pending: VecDeque<PendingQuery>,
+    free: Vec<WebGlQuery>,
+}
+
+impl TimerQueries {
+    /// Returns `None` if the timer query extension is not available.
+    pub fn new(gl: &WebGl2RenderingContext) -> Option<Self> {
+        match gl.get_extension("EXT_disjoint_timer_query_webgl2") {
+            Ok(Some(_)) => Some(TimerQueries::default()),
+            _ => {
+                log::warn!(
+                    "EXT_disjoint_timer_query_webgl2 is not available; timed sections will be ignored."
+                );
+                None
+            }
+        }
+    }
+
+    pub fn begin(&mut self, gl: &WebGl2RenderingContext, label: &str) -> Result<()> {
+        if let Some(active) = &self.active {
+            log::warn!(
+                "Tried to begin timed section {} while {} is still active; timed sections can't be nested.",
+                label,
+                active.label
+            );
+            return Ok(());
+        }
+
+        let query = if let Some(query) = self.free.pop() {
+            query
+        } else {
+            gl.create_query()
+                .ok_or(Error::ObjectCreation(GlObject::Query))?
+        };
+
+        gl.begin_query(TIME_ELAPSED_EXT, &query);
+        self.active = Some(PendingQuery {
+            label: label.to_string(),
+            query,
+        });
+
+        Ok(())
+    }
+
+    pub fn end(&mut self, gl: &WebGl2RenderingContext) {
+        if let Some(active) = self.active.take() {
+            gl.end_query(TIME_ELAPSED_EXT);
+            self.pending.push_back(active);
+        } else {
+            log::warn!("Tried to end a timed section, but none was active.");
+        }
+    }
+
+    pub fn poll(&mut self, gl: &WebGl2RenderingContext) -> Vec<TimedSection> {
+        let mut result = Vec::new();
+
+        // Queries resolve in the order they were issued, so we can stop at
+        // the first one that isn't available yet.
+        while let Some(front) = self.pending.front() {
+            let available = gl
+                .get_query_parameter(&front.query, WebGl2RenderingContext::QUERY_RESULT_AVAILABLE)
+                .as_bool()
+                .unwrap_or(false);
+
+            if !available {
+                break;
+            }
+
+            let PendingQuery { label, query } = self.pending.pop_front().unwrap();
+            let nanoseconds = gl
+                .get_query_parameter(&query, WebGl2RenderingContext::QUERY_RESULT)
+                .as_f64()
+                .unwrap_or(0.) as u64;
+            self.free.push(query);
+
+            result.push(TimedSection { label, nanoseconds });
+        }
+
+        // If a disjoint operation (like a clock change or power event) happened
+        // while queries were in flight, their results are meaningless.
+        let disjoint = gl
+            .get_parameter(GPU_DISJOINT_EXT)
+            .ok()
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if disjoint {
+            log::info!(
+                "GPU timer was disjoint; discarding {} timed sections.",
+                result.len()
+            );
+            result.clear();
+        }
+
+        result
+    }
+}
@@ -26,7 +26,7 @@ impl PartialEq for UniformHandle {
 
 impl Eq for UniformHandle {}
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum UniformValue {
     Float(f32),
     Vec2([f32; 2]),
@@ -89,6 +89,18 @@ impl UniformValueType for f32 {
     }
 }
 
+impl UniformValueType for i32 {
+    fn into_uniform_value(v: &i32) -> UniformValue {
+        UniformValue::Int(*v)
+    }
+}
+
+impl UniformValueType for u32 {
+    fn into_uniform_value(v: &u32) -> UniformValue {
+        UniformValue::UnsignedInt(*v)
+    }
+}
+
 impl UniformValueType for [f32; 2] {
     fn into_uniform_value(v: &[f32; 2]) -> UniformValue {
         UniformValue::Vec2(*v)
@@ -1,8 +1,8 @@
 use crate::webgl::types::DataType;
 use std::collections::BTreeMap;
 
-use super::{buffer::BindResult, state::BufferBinding, BufferHandle};
-use anyhow::anyhow;
+use super::{buffer::BindResult, debug::ErrorCheck, state::BufferBinding, BufferHandle};
+use crate::error::{Error, GlObject, Result};
 use web_sys::WebGlVertexArrayObject;
 
 pub struct VaoHandle {
@@ -11,7 +11,11 @@ pub struct VaoHandle {
 }
 
 impl VaoHandle {
-    pub fn gpu_bind(&mut self, gl: &web_sys::WebGl2RenderingContext) -> anyhow::Result<()> {
+    pub fn gpu_bind(
+        &mut self,
+        gl: &web_sys::WebGl2RenderingContext,
+        check: ErrorCheck,
+    ) -> Result<()> {
         let create = if let Some(vao) = &self.vao {
             gl.bind_vertex_array(Some(vao));
             false
@@ -19,17 +23,29 @@ impl VaoHandle {
             log::info!("Creating Vertex Array.");
             let vao = gl
                 .create_vertex_array()
-                .ok_or_else(|| anyhow!("Couldn't create vertex array."))?;
+                .ok_or(Error::ObjectCreation(GlObject::VertexArray))?;
             gl.bind_vertex_array(Some(&vao));
             self.vao = Some(vao);
             true
         };
+        check.after(|| "binding vertex array".to_string())?;
 
         for (buffer, bindings) in &self.buffers {
             let upsized_buffer = match buffer.bind(gl)? {
                 BindResult::BoundExisting => false,
                 BindResult::BoundNew => true,
             };
+            check.after(|| {
+                let attributes: Vec<String> = bindings
+                    .iter()
+                    .map(|b| check.attribute_name(b.location))
+                    .collect();
+                format!(
+                    "binding buffer of {} bytes for attributes {}",
+                    buffer.byte_len(),
+                    attributes.join(", ")
+                )
+            })?;
 
             if !create && !upsized_buffer {
                 // If this is not a new VAO, and the buffer already existed,
@@ -62,6 +78,16 @@ impl VaoHandle {
                 }
 
                 gl.enable_vertex_attrib_array(binding.location);
+                check.after(|| {
+                    format!(
+                        "binding attribute {} location {} as {:?} (stride {}, offset {})",
+                        check.attribute_name(binding.location),
+                        binding.location,
+                        binding.kind,
+                        binding.stride,
+                        binding.offset
+                    )
+                })?;
             }
         }
 
@@ -0,0 +1,243 @@
+#[cfg(feature = "png")]
+use crate::error::{Error, Result};
+
+/// An RGBA image with 8 bits per channel, with rows ordered top-to-bottom.
+///
+/// Snapshots of the canvas or a render target are taken with
+/// `Renderer::snapshot`, and can be compared against a reference image
+/// with `compare`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+/// The result of comparing two images that did not match.
+#[derive(Clone, Debug)]
+pub struct ImageDiff {
+    /// The number of pixels where some channel differed by more than the tolerance.
+    pub mismatched_pixels: usize,
+
+    /// The largest difference in any channel of any pixel.
+    pub max_difference: u8,
+
+    /// An image highlighting mismatched pixels in red over a faded copy of
+    /// the expected image.
+    pub diff: Snapshot,
+}
+
+impl Snapshot {
+    pub fn new(width: u32, height: u32, data: Vec<u8>) -> Self {
+        assert_eq!(
+            (width * height * 4) as usize,
+            data.len(),
+            "Expected {}x{} RGBA image data.",
+            width,
+            height
+        );
+
+        Snapshot {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Construct a snapshot from pixels as returned by `readPixels`, which
+    /// orders rows bottom-to-top.
+    pub(crate) fn from_gl_rows(width: u32, height: u32, data: Vec<u8>) -> Self {
+        // A hidden or collapsed canvas has no rows to reorder.
+        if width == 0 || height == 0 {
+            return Snapshot::new(width, height, Vec::new());
+        }
+
+        let row_len = (width * 4) as usize;
+        let data = data
+            .chunks_exact(row_len)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
+        Snapshot::new(width, height, data)
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.width + x) * 4) as usize;
+        [
+            self.data[i],
+            self.data[i + 1],
+            self.data[i + 2],
+            self.data[i + 3],
+        ]
+    }
+
+    /// Compare against an expected image, allowing each channel of each pixel
+    /// to differ by up to `tolerance`. Returns `None` if the images match.
+    pub fn compare(&self, expected: &Snapshot, tolerance: u8) -> Option<ImageDiff> {
+        let width = self.width.max(expected.width);
+        let height = self.height.max(expected.height);
+        let mut diff = Vec::with_capacity((width * height * 4) as usize);
+        let mut mismatched_pixels = 0;
+        let mut max_difference = 0;
+
+        for y in 0..height {
+            for x in 0..width {
+                let in_self = x < self.width && y < self.height;
+                let in_expected = x < expected.width && y < expected.height;
+
+                if !(in_self && in_expected) {
+                    // Pixels outside of one image always count as mismatched.
+                    mismatched_pixels += 1;
+                    max_difference = 255;
+                    diff.extend_from_slice(&[255, 0, 0, 255]);
+                    continue;
+                }
+
+                let actual = self.pixel(x, y);
+                let expected = expected.pixel(x, y);
+                let difference = actual
+                    .iter()
+                    .zip(expected.iter())
+                    .map(|(a, e)| a.abs_diff(*e))
+                    .max()
+                    .unwrap_or(0);
+                max_difference = max_difference.max(difference);
+
+                if difference > tolerance {
+                    mismatched_pixels += 1;
+                    diff.extend_from_slice(&[255, 0, 0, 255]);
+                } else {
+                    let luma =
+                        (expected[0] as u32 * 3 + expected[1] as u32 * 6 + expected[2] as u32) / 10;
+                    let faded = (128 + luma / 2) as u8;
+                    diff.extend_from_slice(&[faded, faded, faded, 255]);
+                }
+            }
+        }
+
+        if mismatched_pixels == 0 {
+            None
+        } else {
+            Some(ImageDiff {
+                mismatched_pixels,
+                max_difference,
+                diff: Snapshot::new(width, height, diff),
+            })
+        }
+    }
+
+    #[cfg(feature = "png")]
+    pub fn encode_png(&self) -> Result<Vec<u8>> {
+        let mut result = Vec::new();
+
+        {
+            let mut encoder = png::Encoder::new(&mut result, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder
+                .write_header()
+                .map_err(|e| Error::Image(e.to_string()))?;
+            writer
+                .write_image_data(&self.data)
+                .map_err(|e| Error::Image(e.to_string()))?;
+        }
+
+        Ok(result)
+    }
+
+    /// Decode an 8-bit RGB or RGBA PNG image.
+    #[cfg(feature = "png")]
+    pub fn decode_png(bytes: &[u8]) -> Result<Self> {
+        let decoder = png::Decoder::new(bytes);
+        let mut reader = decoder
+            .read_info()
+            .map_err(|e| Error::Image(e.to_string()))?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|e| Error::Image(e.to_string()))?;
+        buffer.truncate(info.buffer_size());
+
+        if info.bit_depth != png::BitDepth::Eight {
+            return Err(Error::Image(format!(
+                "Expected an 8-bit PNG, found {:?}.",
+                info.bit_depth
+            )));
+        }
+
+        let data = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            color_type => {
+                return Err(Error::Image(format!(
+                    "Expected an RGB or RGBA PNG, found {:?}.",
+                    color_type
+                )))
+            }
+        };
+
+        Ok(Snapshot::new(info.width, info.height, data))
+    }
+}
+
+/// Panic if `actual` does not match the PNG image `reference_png` within
+/// `tolerance`.
+///
+/// On failure, an image highlighting the mismatched pixels is written to
+/// `diff_path` before panicking. Writing the diff is best-effort, since
+/// there is no filesystem when running in a browser.
+#[cfg(feature = "png")]
+pub fn assert_matches_reference(
+    actual: &Snapshot,
+    reference_png: &[u8],
+    diff_path: impl AsRef<std::path::Path>,
+    tolerance: u8,
+) {
+    let expected = Snapshot::decode_png(reference_png).expect("Couldn't decode reference image.");
+
+    if let Some(diff) = actual.compare(&expected, tolerance) {
+        let diff_path = diff_path.as_ref();
+        let written = diff.diff.encode_png().and_then(|png| {
+            std::fs::write(diff_path, png).map_err(|e| Error::Image(e.to_string()))
+        });
+
+        if let Err(error) = written {
+            log::warn!("Couldn't write diff image to {:?}: {}", diff_path, error);
+        }
+
+        panic!(
+            "Image did not match reference: {} pixels differ by more than {} (max difference {}). \
+            See diff image at {:?}.",
+            diff.mismatched_pixels, tolerance, diff.max_difference, diff_path
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_gl_rows_flips_rows() {
+        let data = vec![1, 1, 1, 1, 2, 2, 2, 2];
+        let snapshot = Snapshot::from_gl_rows(1, 2, data);
+
+        assert_eq!([2; 4], snapshot.pixel(0, 0));
+        assert_eq!([1; 4], snapshot.pixel(0, 1));
+    }
+
+    #[test]
+    fn test_from_gl_rows_of_empty_canvas() {
+        for (width, height) in [(0, 0), (0, 3), (3, 0)] {
+            let snapshot = Snapshot::from_gl_rows(width, height, Vec::new());
+
+            assert_eq!((width, height), (snapshot.width, snapshot.height));
+            assert!(snapshot.data.is_empty());
+        }
+    }
+}
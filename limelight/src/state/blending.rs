@@ -57,7 +57,7 @@ impl Default for BlendEquation {
     }
 }
 
-#[derive(Default, Clone, PartialEq)]
+#[derive(Default, Clone, Debug, PartialEq)]
 pub struct BlendFunction {
     pub source_factor: BlendingFactorSrc,
     pub dst_factor: BlendingFactorDest,
@@ -65,7 +65,7 @@ pub struct BlendFunction {
 }
 
 impl GpuBind for Option<BlendFunction> {
-    fn gpu_bind(&self, gl: &web_sys::WebGl2RenderingContext) -> anyhow::Result<()> {
+    fn gpu_bind(&self, gl: &web_sys::WebGl2RenderingContext) -> crate::Result<()> {
         match self {
             Some(blend) => {
                 gl.blend_func(blend.source_factor as _, blend.dst_factor as _);
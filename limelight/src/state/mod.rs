@@ -10,4 +10,8 @@ pub struct StateDescriptor {
     pub blend_func: Option<BlendFunction>,
     pub culling: Option<CullingMode>,
     pub depth_func: Option<DepthFunction>,
+
+    /// Discard primitives before rasterization. Useful for transform
+    /// feedback passes that only need the vertex shader output.
+    pub rasterizer_discard: bool,
 }
@@ -0,0 +1,119 @@
+use bytemuck::Pod;
+
+use crate::{
+    error::{Error, Result},
+    shadow_gpu::TextureHandle,
+    webgl::texture::{PixelFormat, TextureKind},
+};
+
+/// A texture that can be sampled in shaders, attached to a program with
+/// `Program::with_texture`.
+///
+/// Like buffers, the GL texture is created lazily, and data set on it is
+/// uploaded the next time it is drawn with. Texture arrays and 3D textures
+/// have a number of layers which can be set independently with `set_layer`.
+#[derive(Clone)]
+pub struct Texture {
+    handle: TextureHandle,
+}
+
+impl Texture {
+    /// Create a 2D texture, read with a `sampler2D` (or `usampler2D`, for
+    /// integer formats) uniform.
+    pub fn new(width: i32, height: i32, format: PixelFormat) -> Self {
+        Texture {
+            handle: TextureHandle::new(width, height, format),
+        }
+    }
+
+    /// Create a texture array with `layers` layers, read with a
+    /// `sampler2DArray` uniform.
+    pub fn new_array(width: i32, height: i32, layers: i32, format: PixelFormat) -> Self {
+        Texture {
+            handle: TextureHandle::new_layered(
+                TextureKind::Texture2DArray,
+                width,
+                height,
+                layers,
+                format,
+            ),
+        }
+    }
+
+    /// Create a 3D texture, read with a `sampler3D` uniform.
+    pub fn new_3d(width: i32, height: i32, depth: i32, format: PixelFormat) -> Self {
+        Texture {
+            handle: TextureHandle::new_layered(
+                TextureKind::Texture3D,
+                width,
+                height,
+                depth,
+                format,
+            ),
+        }
+    }
+
+    pub fn kind(&self) -> TextureKind {
+        self.handle.kind()
+    }
+
+    pub fn width(&self) -> i32 {
+        self.handle.width()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.handle.height()
+    }
+
+    /// The number of layers of a texture array or depth of a 3D texture.
+    /// This is 1 for 2D textures.
+    pub fn layers(&self) -> i32 {
+        self.handle.depth()
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.handle.format()
+    }
+
+    /// Set the pixels of every layer. Layers are stored one after the other,
+    /// and rows within each layer from the bottom up, as in GL.
+    pub fn set_data<T: Pod>(&self, data: &[T]) -> Result<()> {
+        self.upload(0, self.layers(), bytemuck::cast_slice(data))
+    }
+
+    /// Set the pixels of a single layer, leaving the others unchanged.
+    pub fn set_layer<T: Pod>(&self, layer: i32, data: &[T]) -> Result<()> {
+        if layer < 0 || layer >= self.layers() {
+            return Err(Error::InvalidUsage(format!(
+                "Layer {} is out of range for a texture with {} layers.",
+                layer,
+                self.layers()
+            )));
+        }
+
+        self.upload(layer, 1, bytemuck::cast_slice(data))
+    }
+
+    fn upload(&self, first_layer: i32, layers: i32, data: &[u8]) -> Result<()> {
+        let expected =
+            (self.width() * self.height() * layers) as usize * self.format().bytes_per_pixel();
+        if data.len() != expected {
+            return Err(Error::InvalidUsage(format!(
+                "Expected {} bytes of pixel data for {} layer(s) of a {}x{} {:?} texture, got {}.",
+                expected,
+                layers,
+                self.width(),
+                self.height(),
+                self.format(),
+                data.len()
+            )));
+        }
+
+        self.handle.upload(first_layer, layers, data.to_vec());
+        Ok(())
+    }
+
+    pub(crate) fn handle(&self) -> &TextureHandle {
+        &self.handle
+    }
+}
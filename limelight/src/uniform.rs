@@ -1,9 +1,23 @@
 use crate::shadow_gpu::{UniformValue, UniformValueType};
-use std::{cell::RefCell, fmt::Debug, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt::Debug,
+    rc::Rc,
+};
+
+#[derive(Debug)]
+struct UniformInner<T: UniformValueType> {
+    value: RefCell<T>,
+
+    /// Incremented whenever the value is set, so that programs can tell
+    /// whether they need to upload it again without reading the value.
+    version: Cell<u64>,
+}
 
 #[derive(Debug, Clone)]
 pub struct Uniform<T: UniformValueType> {
-    value: Rc<RefCell<T>>,
+    inner: Rc<UniformInner<T>>,
 }
 
 impl Uniform<[[f32; 4]; 4]> {
@@ -22,21 +36,81 @@ impl Uniform<[[f32; 4]; 4]> {
 impl<T: UniformValueType> Uniform<T> {
     pub fn new(value: T) -> Uniform<T> {
         Uniform {
-            value: Rc::new(RefCell::new(value)),
+            inner: Rc::new(UniformInner {
+                value: RefCell::new(value),
+                version: Cell::new(0),
+            }),
         }
     }
 
+    pub fn value(&self) -> T {
+        self.inner.value.borrow().clone()
+    }
+
     pub fn set_value(&self, value: T) {
-        *self.value.borrow_mut() = value
+        *self.inner.value.borrow_mut() = value;
+        self.inner.version.set(self.inner.version.get() + 1);
     }
 }
 
+/// Looks up the current value of a uniform by name, as the `Renderer` does
+/// for its global and built-in uniforms. Lets CPU code such as hit testing
+/// see the same values that programs would receive.
+pub trait UniformSource {
+    fn uniform_value(&self, name: &str) -> Option<UniformValue>;
+}
+
 pub trait GenericUniform {
     fn get_value(&self) -> UniformValue;
+
+    /// A counter that changes whenever the value does.
+    fn version(&self) -> u64;
 }
 
 impl<T: UniformValueType> GenericUniform for Uniform<T> {
     fn get_value(&self) -> UniformValue {
-        UniformValueType::into_uniform_value(&*self.value.borrow())
+        UniformValueType::into_uniform_value(&*self.inner.value.borrow())
+    }
+
+    fn version(&self) -> u64 {
+        self.inner.version.get()
+    }
+}
+
+struct GlobalUniform {
+    /// Distinguishes uniforms registered under the same name, since their
+    /// versions are counted independently.
+    entry: u64,
+    uniform: Box<dyn GenericUniform>,
+}
+
+/// Uniforms registered on the `Renderer` by name.
+#[derive(Default)]
+pub(crate) struct GlobalUniforms {
+    uniforms: HashMap<String, GlobalUniform>,
+    next_entry: u64,
+}
+
+impl GlobalUniforms {
+    pub fn insert(&mut self, name: &str, uniform: Box<dyn GenericUniform>) {
+        self.next_entry += 1;
+        self.uniforms.insert(
+            name.to_string(),
+            GlobalUniform {
+                entry: self.next_entry,
+                uniform,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.uniforms.remove(name).is_some()
+    }
+
+    /// Returns the registration entry and the uniform registered under `name`.
+    pub fn get(&self, name: &str) -> Option<(u64, &dyn GenericUniform)> {
+        self.uniforms
+            .get(name)
+            .map(|global| (global.entry, global.uniform.as_ref()))
     }
 }
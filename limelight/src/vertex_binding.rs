@@ -0,0 +1,125 @@
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
+
+use crate::{
+    buffer::{BufferLike, DummyBuffer},
+    error::{Error, Result},
+    program::ProgramLike,
+    renderer::BufferBindingGroup,
+    shadow_gpu::{BufferHandle, ProgramHandle, VaoHandle},
+    Attribute, Renderer,
+};
+
+enum VertexCount {
+    /// Use the current length of a buffer, which may change between draws.
+    Buffer(BufferHandle),
+
+    /// A fixed count, from a buffer like `DummyBuffer` without GPU storage.
+    Fixed(usize),
+}
+
+impl VertexCount {
+    fn new<T: Attribute>(buffer: &impl BufferLike<T>) -> Self {
+        match buffer.get_buffer() {
+            Some(handle) => VertexCount::Buffer(handle),
+            None => VertexCount::Fixed(buffer.len()),
+        }
+    }
+
+    fn get(&self) -> usize {
+        match self {
+            VertexCount::Buffer(handle) => handle.len(),
+            VertexCount::Fixed(count) => *count,
+        }
+    }
+}
+
+/// The attribute bindings between a program and its vertex (and optionally
+/// instance) buffers, resolved once and kept in a vertex array object.
+///
+/// `Renderer::render` and `Renderer::render_instanced` work out these
+/// bindings on every draw and look the VAO up in a cache. For scenes with
+/// many draws, create a `VertexBinding` up front and draw it with
+/// `Renderer::render_binding` instead.
+///
+/// The binding keeps the buffers it was created with; updating their data
+/// with `set_data` is picked up by later draws, and the draw counts follow
+/// the buffers' current lengths.
+pub struct VertexBinding<T: Attribute, I: Attribute> {
+    program: ProgramHandle,
+    vao: Rc<RefCell<VaoHandle>>,
+    vertices: VertexCount,
+    instances: Option<VertexCount>,
+    _ph: PhantomData<T>,
+    _phi: PhantomData<I>,
+}
+
+impl<T: Attribute> VertexBinding<T, ()> {
+    pub fn new(
+        renderer: &mut Renderer,
+        program: &mut impl ProgramLike<T, ()>,
+        vertex_buffer: &impl BufferLike<T>,
+    ) -> Result<Self> {
+        Self::create(renderer, program, vertex_buffer, None::<&DummyBuffer>)
+    }
+}
+
+impl<T: Attribute, I: Attribute> VertexBinding<T, I> {
+    pub fn new_instanced(
+        renderer: &mut Renderer,
+        program: &mut impl ProgramLike<T, I>,
+        vertex_buffer: &impl BufferLike<T>,
+        instance_buffer: &impl BufferLike<I>,
+    ) -> Result<Self> {
+        Self::create(renderer, program, vertex_buffer, Some(instance_buffer))
+    }
+
+    fn create(
+        renderer: &mut Renderer,
+        program: &mut impl ProgramLike<T, I>,
+        vertex_buffer: &impl BufferLike<T>,
+        instance_buffer: Option<&impl BufferLike<I>>,
+    ) -> Result<Self> {
+        let bound_program = program.get_program(renderer.gpu())?;
+
+        if bound_program.transform_feedback().is_some() {
+            return Err(Error::InvalidUsage(
+                "Vertex bindings can't be used with transform feedback programs.".to_string(),
+            ));
+        }
+
+        let mut bg = BufferBindingGroup::new(bound_program.attributes().clone());
+        bg.add_buffer(vertex_buffer, 0);
+        if let Some(instance_buffer) = instance_buffer {
+            bg.add_buffer(instance_buffer, 1);
+        }
+
+        Ok(VertexBinding {
+            program: bound_program.handle(),
+            vao: Rc::new(RefCell::new(VaoHandle {
+                buffers: bg.bindings,
+                vao: None,
+            })),
+            vertices: VertexCount::new(vertex_buffer),
+            instances: instance_buffer.map(VertexCount::new),
+            _ph: PhantomData,
+            _phi: PhantomData,
+        })
+    }
+
+    pub(crate) fn program(&self) -> &ProgramHandle {
+        &self.program
+    }
+
+    pub(crate) fn vao(&self) -> Rc<RefCell<VaoHandle>> {
+        self.vao.clone()
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.get()
+    }
+
+    /// The number of instances drawn, or `None` if the binding isn't instanced.
+    pub fn instance_count(&self) -> Option<usize> {
+        self.instances.as_ref().map(VertexCount::get)
+    }
+}
@@ -7,6 +7,7 @@
 pub enum BufferBindPoint {
     ArrayBuffer = 0x8892,
     ElementArrayBuffer = 0x8893,
+    TransformFeedbackBuffer = 0x8C8E,
 }
 
 /// Usage hint to tell WebGL how a buffer will be used.
@@ -26,3 +27,14 @@ pub enum BufferUsageHint {
     /// Hint that a buffer is written and read many times.
     DynamicDraw = 0x88E8,
 }
+
+/// How transform feedback varyings are laid out in the output buffers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u32)]
+pub enum FeedbackBufferMode {
+    /// All varyings are written, interleaved, into a single buffer.
+    Interleaved = 0x8C8C,
+
+    /// Each varying is written into its own buffer.
+    Separate = 0x8C8D,
+}
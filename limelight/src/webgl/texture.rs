@@ -0,0 +1,137 @@
+use super::types::DataType;
+
+/// The kinds of texture that can be created, and the sampler types used to
+/// read them in GLSL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextureKind {
+    /// A single image, read with `sampler2D`.
+    Texture2D,
+
+    /// A stack of same-sized images, read with `sampler2DArray`. The layer is
+    /// selected with the third texture coordinate, which is not interpolated
+    /// between layers.
+    Texture2DArray,
+
+    /// A volume, read with `sampler3D`. All three texture coordinates are
+    /// normalized and filtered.
+    Texture3D,
+}
+
+impl TextureKind {
+    /// The target the texture is bound to.
+    pub fn target(&self) -> u32 {
+        match self {
+            TextureKind::Texture2D => 0x0DE1,
+            TextureKind::Texture2DArray => 0x8C1A,
+            TextureKind::Texture3D => 0x806F,
+        }
+    }
+}
+
+/// Storage formats for textures and render targets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PixelFormat {
+    /// Four 8-bit normalized channels.
+    Rgba8,
+
+    /// One 8-bit normalized channel, e.g. for masks and distance fields.
+    /// Uploaded rows are tightly packed, so the width should be a multiple
+    /// of 4.
+    R8,
+
+    /// One 32-bit unsigned integer channel.
+    R32ui,
+
+    /// Two 32-bit unsigned integer channels.
+    Rg32ui,
+
+    /// Four 32-bit unsigned integer channels.
+    Rgba32ui,
+}
+
+impl PixelFormat {
+    /// The sized internal format passed to `texStorage2D`.
+    pub fn internal_format(&self) -> u32 {
+        match self {
+            PixelFormat::Rgba8 => 0x8058,
+            PixelFormat::R8 => 0x8229,
+            PixelFormat::R32ui => 0x8236,
+            PixelFormat::Rg32ui => 0x823C,
+            PixelFormat::Rgba32ui => 0x8D70,
+        }
+    }
+
+    /// The unsized format used when uploading pixel data.
+    pub fn format(&self) -> u32 {
+        match self {
+            PixelFormat::Rgba8 => 0x1908,
+            PixelFormat::R8 => 0x1903,
+            PixelFormat::R32ui => 0x8D94,
+            PixelFormat::Rg32ui => 0x8228,
+            PixelFormat::Rgba32ui => 0x8D99,
+        }
+    }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            PixelFormat::Rgba8 | PixelFormat::R8 => DataType::UnsignedByte,
+            _ => DataType::UnsignedInt,
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        match self {
+            PixelFormat::R8 | PixelFormat::R32ui => 1,
+            PixelFormat::Rg32ui => 2,
+            PixelFormat::Rgba8 | PixelFormat::Rgba32ui => 4,
+        }
+    }
+
+    pub fn bytes_per_pixel(&self) -> usize {
+        self.channels() * self.data_type().size() as usize
+    }
+
+    /// Integer formats are not normalized, and can't be filtered or blended.
+    pub fn is_integer(&self) -> bool {
+        self.data_type() != DataType::UnsignedByte
+    }
+
+    /// The format that pixels of this type can always be read back as.
+    pub fn read_format(&self) -> ReadFormat {
+        if self.is_integer() {
+            ReadFormat::RgbaInteger
+        } else {
+            ReadFormat::Rgba
+        }
+    }
+}
+
+/// Format and type combinations that WebGL2 guarantees `readPixels` supports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReadFormat {
+    /// `RGBA` / `UNSIGNED_BYTE`, for the default framebuffer and normalized targets.
+    Rgba,
+
+    /// `RGBA_INTEGER` / `UNSIGNED_INT`, for unsigned integer targets.
+    RgbaInteger,
+}
+
+impl ReadFormat {
+    pub fn format(&self) -> u32 {
+        match self {
+            ReadFormat::Rgba => 0x1908,
+            ReadFormat::RgbaInteger => 0x8D99,
+        }
+    }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ReadFormat::Rgba => DataType::UnsignedByte,
+            ReadFormat::RgbaInteger => DataType::UnsignedInt,
+        }
+    }
+
+    pub fn bytes_per_pixel(&self) -> usize {
+        4 * self.data_type().size() as usize
+    }
+}
@@ -1,4 +1,4 @@
-use anyhow::anyhow;
+use crate::error::Error;
 
 #[derive(Clone, Copy, Debug, PartialEq, Hash, Eq)]
 #[repr(u32)]
@@ -13,7 +13,7 @@ pub enum DataType {
 }
 
 impl TryFrom<u32> for DataType {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(value: u32) -> Result<Self, Self::Error> {
         match value {
@@ -24,7 +24,7 @@ impl TryFrom<u32> for DataType {
             0x1404 => Ok(DataType::Int),
             0x1405 => Ok(DataType::UnsignedInt),
             0x1406 => Ok(DataType::Float),
-            _ => Err(anyhow!("Invalid DataType.")),
+            _ => Err(Error::UnsupportedType { value }),
         }
     }
 }
@@ -222,7 +222,23 @@ pub enum GlSizedDataType {
     FloatMat3 = 0x8B5B,
     FloatMat4 = 0x8B5C,
     Sampler2D = 0x8B5E,
+    Sampler3D = 0x8B5F,
     SamplerCube = 0x8B60,
+    Sampler2DShadow = 0x8B62,
+    Sampler2DArray = 0x8DC1,
+    Sampler2DArrayShadow = 0x8DC4,
+    SamplerCubeShadow = 0x8DC5,
+    UnsignedIntVec2 = 0x8DC6,
+    UnsignedIntVec3 = 0x8DC7,
+    UnsignedIntVec4 = 0x8DC8,
+    IntSampler2D = 0x8DCA,
+    IntSampler3D = 0x8DCB,
+    IntSamplerCube = 0x8DCC,
+    IntSampler2DArray = 0x8DCF,
+    UnsignedIntSampler2D = 0x8DD2,
+    UnsignedIntSampler3D = 0x8DD3,
+    UnsignedIntSamplerCube = 0x8DD4,
+    UnsignedIntSampler2DArray = 0x8DD7,
 }
 
 impl GlSizedDataType {
@@ -232,9 +248,12 @@ impl GlSizedDataType {
             GlSizedDataType::FloatVec2 => SizedDataType::new(DataType::Float, 2),
             GlSizedDataType::FloatVec3 => SizedDataType::new(DataType::Float, 3),
             GlSizedDataType::FloatVec4 => SizedDataType::new(DataType::Float, 4),
-            GlSizedDataType::IntVec2 => todo!(),
-            GlSizedDataType::IntVec3 => todo!(),
-            GlSizedDataType::IntVec4 => todo!(),
+            GlSizedDataType::IntVec2 => SizedDataType::new(DataType::Int, 2),
+            GlSizedDataType::IntVec3 => SizedDataType::new(DataType::Int, 3),
+            GlSizedDataType::IntVec4 => SizedDataType::new(DataType::Int, 4),
+            GlSizedDataType::UnsignedIntVec2 => SizedDataType::new(DataType::UnsignedInt, 2),
+            GlSizedDataType::UnsignedIntVec3 => SizedDataType::new(DataType::UnsignedInt, 3),
+            GlSizedDataType::UnsignedIntVec4 => SizedDataType::new(DataType::UnsignedInt, 4),
             GlSizedDataType::Bool => todo!(),
             GlSizedDataType::BoolVec2 => todo!(),
             GlSizedDataType::BoolVec3 => todo!(),
@@ -242,8 +261,22 @@ impl GlSizedDataType {
             GlSizedDataType::FloatMat2 => todo!(),
             GlSizedDataType::FloatMat3 => todo!(),
             GlSizedDataType::FloatMat4 => todo!(),
-            GlSizedDataType::Sampler2D => todo!(),
-            GlSizedDataType::SamplerCube => todo!(),
+            // Samplers are set to the index of a texture unit.
+            GlSizedDataType::Sampler2D
+            | GlSizedDataType::Sampler3D
+            | GlSizedDataType::SamplerCube
+            | GlSizedDataType::Sampler2DShadow
+            | GlSizedDataType::Sampler2DArray
+            | GlSizedDataType::Sampler2DArrayShadow
+            | GlSizedDataType::SamplerCubeShadow
+            | GlSizedDataType::IntSampler2D
+            | GlSizedDataType::IntSampler3D
+            | GlSizedDataType::IntSamplerCube
+            | GlSizedDataType::IntSampler2DArray
+            | GlSizedDataType::UnsignedIntSampler2D
+            | GlSizedDataType::UnsignedIntSampler3D
+            | GlSizedDataType::UnsignedIntSamplerCube
+            | GlSizedDataType::UnsignedIntSampler2DArray => SizedDataType::new(DataType::Int, 1),
             GlSizedDataType::Byte => todo!(),
             GlSizedDataType::UnsignedByte => todo!(),
             GlSizedDataType::Short => todo!(),
@@ -255,7 +288,7 @@ impl GlSizedDataType {
 }
 
 impl TryFrom<u32> for GlSizedDataType {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(value: u32) -> Result<Self, Self::Error> {
         match value {
@@ -280,8 +313,24 @@ impl TryFrom<u32> for GlSizedDataType {
             0x8B5B => Ok(GlSizedDataType::FloatMat3),
             0x8B5C => Ok(GlSizedDataType::FloatMat4),
             0x8B5E => Ok(GlSizedDataType::Sampler2D),
+            0x8B5F => Ok(GlSizedDataType::Sampler3D),
             0x8B60 => Ok(GlSizedDataType::SamplerCube),
-            _ => Err(anyhow::anyhow!("Unexpected uniform type: {}", value)),
+            0x8B62 => Ok(GlSizedDataType::Sampler2DShadow),
+            0x8DC1 => Ok(GlSizedDataType::Sampler2DArray),
+            0x8DC4 => Ok(GlSizedDataType::Sampler2DArrayShadow),
+            0x8DC5 => Ok(GlSizedDataType::SamplerCubeShadow),
+            0x8DC6 => Ok(GlSizedDataType::UnsignedIntVec2),
+            0x8DC7 => Ok(GlSizedDataType::UnsignedIntVec3),
+            0x8DC8 => Ok(GlSizedDataType::UnsignedIntVec4),
+            0x8DCA => Ok(GlSizedDataType::IntSampler2D),
+            0x8DCB => Ok(GlSizedDataType::IntSampler3D),
+            0x8DCC => Ok(GlSizedDataType::IntSamplerCube),
+            0x8DCF => Ok(GlSizedDataType::IntSampler2DArray),
+            0x8DD2 => Ok(GlSizedDataType::UnsignedIntSampler2D),
+            0x8DD3 => Ok(GlSizedDataType::UnsignedIntSampler3D),
+            0x8DD4 => Ok(GlSizedDataType::UnsignedIntSamplerCube),
+            0x8DD7 => Ok(GlSizedDataType::UnsignedIntSampler2DArray),
+            _ => Err(Error::UnsupportedType { value }),
         }
     }
 }
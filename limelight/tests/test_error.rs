@@ -0,0 +1,32 @@
+use limelight::error::{Severity, ShaderMessage};
+
+#[test]
+fn test_parse_shader_log() {
+    let log = "ERROR: 0:12: 'x' : undeclared identifier\n\
+        WARNING: 0:3: extension directive should occur before any non-preprocessor tokens\n\
+        ERROR: 2 compilation errors.  No code generated.\n\0";
+
+    let messages = ShaderMessage::parse_log(log);
+
+    assert_eq!(
+        vec![
+            ShaderMessage {
+                severity: Severity::Error,
+                line: Some(12),
+                message: "'x' : undeclared identifier".to_string(),
+            },
+            ShaderMessage {
+                severity: Severity::Warning,
+                line: Some(3),
+                message: "extension directive should occur before any non-preprocessor tokens"
+                    .to_string(),
+            },
+            ShaderMessage {
+                severity: Severity::Error,
+                line: None,
+                message: "2 compilation errors.  No code generated.".to_string(),
+            },
+        ],
+        messages
+    );
+}
@@ -0,0 +1,27 @@
+#![cfg(feature = "glam")]
+
+use glam::{Mat4, Vec2, Vec3};
+use limelight::{shadow_gpu::UniformValue, uniform::GenericUniform, AsSizedDataType, Uniform};
+
+#[test]
+fn test_glam_matrix_uploads_rows() {
+    let uniform = Uniform::new(Mat4::from_translation(Vec3::new(1., 2., 3.)));
+
+    assert_eq!(
+        UniformValue::Mat4([
+            [1., 0., 0., 1.],
+            [0., 1., 0., 2.],
+            [0., 0., 1., 3.],
+            [0., 0., 0., 1.],
+        ]),
+        uniform.get_value()
+    );
+}
+
+#[test]
+fn test_glam_vector_data_type() {
+    assert_eq!(
+        <[f32; 2]>::as_sized_data_type().size(),
+        Vec2::as_sized_data_type().size()
+    );
+}
@@ -0,0 +1,13 @@
+use limelight::picking::picking_source;
+
+#[test]
+fn test_picking_source_defines_after_version() {
+    assert_eq!(
+        "#version 300 es\n#define PICKING\nvoid main() {}",
+        picking_source("#version 300 es\nvoid main() {}")
+    );
+    assert_eq!(
+        "#define PICKING\nvoid main() {}",
+        picking_source("void main() {}")
+    );
+}
@@ -0,0 +1,16 @@
+use limelight::{PixelFormat, RenderTarget};
+
+#[test]
+fn test_multisampled_target() {
+    let target = RenderTarget::new_multisampled(64, 32, PixelFormat::Rgba8, 4).unwrap();
+
+    assert!(target.is_multisampled());
+    assert_eq!(4, target.samples());
+    assert_eq!((64, 32), (target.width(), target.height()));
+}
+
+#[test]
+fn test_integer_target_cannot_be_multisampled() {
+    assert!(RenderTarget::new_multisampled(64, 32, PixelFormat::R32ui, 4).is_err());
+    assert!(!RenderTarget::new(64, 32, PixelFormat::R32ui).is_multisampled());
+}
@@ -0,0 +1,55 @@
+use limelight::Snapshot;
+
+fn solid(width: u32, height: u32, color: [u8; 4]) -> Snapshot {
+    let data = (0..width * height).flat_map(|_| color).collect();
+    Snapshot::new(width, height, data)
+}
+
+#[test]
+fn test_identical_images_match() {
+    let image = solid(4, 3, [10, 20, 30, 255]);
+
+    assert!(image.compare(&image.clone(), 0).is_none());
+}
+
+#[test]
+fn test_differences_within_tolerance_match() {
+    let actual = solid(4, 3, [10, 20, 30, 255]);
+    let expected = solid(4, 3, [12, 18, 30, 255]);
+
+    assert!(actual.compare(&expected, 2).is_none());
+    assert!(actual.compare(&expected, 1).is_some());
+}
+
+#[test]
+fn test_mismatched_pixels_are_counted() {
+    let mut actual = solid(4, 3, [0, 0, 0, 255]);
+    let expected = actual.clone();
+    actual.data[4..8].copy_from_slice(&[100, 0, 0, 255]);
+
+    let diff = actual.compare(&expected, 5).unwrap();
+    assert_eq!(1, diff.mismatched_pixels);
+    assert_eq!(100, diff.max_difference);
+    assert_eq!([255, 0, 0, 255], diff.diff.pixel(1, 0));
+    assert_ne!([255, 0, 0, 255], diff.diff.pixel(0, 0));
+}
+
+#[test]
+fn test_size_mismatch_fails() {
+    let actual = solid(4, 3, [0, 0, 0, 255]);
+    let expected = solid(4, 4, [0, 0, 0, 255]);
+
+    let diff = actual.compare(&expected, 255).unwrap();
+    assert_eq!(4, diff.mismatched_pixels);
+    assert_eq!((4, 4), (diff.diff.width, diff.diff.height));
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_png_round_trip() {
+    let mut image = solid(3, 2, [1, 2, 3, 4]);
+    image.data[0] = 200;
+
+    let png = image.encode_png().unwrap();
+    assert_eq!(image, Snapshot::decode_png(&png).unwrap());
+}
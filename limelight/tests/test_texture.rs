@@ -0,0 +1,33 @@
+use limelight::{webgl::types::GlSizedDataType, Error, PixelFormat, Texture, TextureKind};
+
+#[test]
+fn test_set_layer_checks_range_and_length() {
+    let texture = Texture::new_array(2, 2, 3, PixelFormat::Rgba8);
+    assert_eq!(TextureKind::Texture2DArray, texture.kind());
+
+    assert!(texture.set_layer(2, &[0u8; 16]).is_ok());
+    assert!(matches!(
+        texture.set_layer(3, &[0u8; 16]),
+        Err(Error::InvalidUsage(_))
+    ));
+    assert!(matches!(
+        texture.set_layer(0, &[0u8; 12]),
+        Err(Error::InvalidUsage(_))
+    ));
+}
+
+#[test]
+fn test_set_data_covers_all_layers() {
+    let texture = Texture::new_3d(2, 2, 2, PixelFormat::R32ui);
+
+    assert!(texture.set_data(&[0u32; 8]).is_ok());
+    assert!(texture.set_data(&[0u32; 4]).is_err());
+}
+
+#[test]
+fn test_sampler_types_are_recognized() {
+    // sampler2DArray, sampler3D, usampler2DArray
+    for value in [0x8DC1, 0x8B5F, 0x8DD7] {
+        assert!(GlSizedDataType::try_from(value).is_ok());
+    }
+}
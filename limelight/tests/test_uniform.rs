@@ -0,0 +1,21 @@
+use limelight::{uniform::GenericUniform, Uniform};
+
+#[test]
+fn test_set_value_changes_version() {
+    let uniform = Uniform::new(1.0f32);
+    let initial = uniform.version();
+
+    uniform.set_value(2.0);
+
+    assert_ne!(initial, uniform.version());
+}
+
+#[test]
+fn test_clones_share_version() {
+    let uniform = Uniform::new([0.0f32, 0.0]);
+    let clone = uniform.clone();
+
+    clone.set_value([1.0, 2.0]);
+
+    assert_eq!(clone.version(), uniform.version());
+}
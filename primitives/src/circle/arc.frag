@@ -0,0 +1,59 @@
+#version 300 es
+
+precision highp float;
+
+flat in uint v_color;
+flat in float v_radius;
+flat in float v_inner_radius;
+flat in float v_stroke_width;
+flat in vec2 v_half_angle;
+in vec2 v_coord;
+
+#ifdef PICKING
+flat in uint v_instance;
+uniform uint u_pick_id;
+out uvec4 f_id;
+#else
+out vec4 f_color;
+#endif
+
+// Signed distance to the arc, ring or pie slice; see `CircleArc::distance`.
+float arc_distance(vec2 p) {
+    float r = length(p);
+    float d = max(r - v_radius, v_inner_radius - r);
+
+    if (v_half_angle.y > -0.9999) {
+        // Distance to the wedge between the arc's start and end angles.
+        vec2 c = v_half_angle;
+        p.x = abs(p.x);
+        float wedge = length(p - c * max(dot(p, c), 0.)) * sign(c.y * p.x - c.x * p.y);
+        d = max(d, wedge);
+    }
+
+    if (v_stroke_width > 0.) {
+        d = abs(d + v_stroke_width / 2.) - v_stroke_width / 2.;
+    }
+
+    return d;
+}
+
+void main() {
+    float d = arc_distance(v_coord);
+    float delta = fwidth(d);
+
+    float alpha = 1.0 - smoothstep(-delta, 0., d);
+
+    if (alpha < 0.01) {
+        discard;
+    }
+
+#ifdef PICKING
+    f_id = uvec4(u_pick_id, v_instance, 0u, 0u);
+#else
+    f_color = vec4(
+        float((v_color & 0x000000FFu)) / 255.,
+        float((v_color & 0x0000FF00u) >> 8) / 255.,
+        float((v_color & 0x00FF0000u) >> 16) / 255.,
+        float((v_color & 0xFF000000u) >> 24) / 255.) * alpha;
+#endif
+}
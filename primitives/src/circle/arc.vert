@@ -0,0 +1,49 @@
+#version 300 es
+
+in vec2 position;
+in uint color;
+in float radius;
+in float inner_radius;
+in float start_angle;
+in float end_angle;
+in float stroke_width;
+in vec2 relative_position;
+
+flat out uint v_color;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
+flat out float v_radius;
+flat out float v_inner_radius;
+flat out float v_stroke_width;
+// Sine and cosine of half the angle the arc spans.
+flat out vec2 v_half_angle;
+// Offset from the center in data units, rotated so that the middle of the
+// arc points along +y.
+out vec2 v_coord;
+
+uniform mat4 u_transform;
+
+const float PI = 3.14159265;
+
+void main() {
+    gl_Position = vec4(position + radius * relative_position, 0., 1.) * u_transform;
+
+    float half_angle = min(abs(end_angle - start_angle) / 2., PI);
+    float rotation = PI / 2. - (start_angle + end_angle) / 2.;
+    vec2 offset = radius * relative_position;
+    v_coord = vec2(
+        cos(rotation) * offset.x - sin(rotation) * offset.y,
+        sin(rotation) * offset.x + cos(rotation) * offset.y
+    );
+
+    v_half_angle = vec2(sin(half_angle), cos(half_angle));
+    v_radius = radius;
+    v_inner_radius = inner_radius;
+    v_stroke_width = stroke_width;
+    v_color = color;
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
+}
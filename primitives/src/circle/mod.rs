@@ -1,27 +1,122 @@
-use crate::{color::Color, common::{RelativePosition, identity_quad}};
-use anyhow::Result;
+use crate::{color::Color, hit_test::{to_data_space, HitIndex, HitShape}, common::{sealed::InstanceShaders, layer_program, picking_program, RelativePosition, identity_quad}};
 use limelight::{
     attribute,
+    Attribute,
+    picking::Pickable,
     renderer::Drawable,
-    state::{
-        blending::{BlendFunction, BlendingFactorDest, BlendingFactorSrc},
-        StateDescriptor,
-    },
-    Buffer, BufferUsageHint, DrawMode, Program, Uniform,
+    Buffer, BufferUsageHint, DrawMode, Program, Result, Uniform, UniformSource,
 };
 
 #[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Circle {
     pub position: [f32; 2],
     pub radius: f32,
     pub color: Color,
 }
 
-pub struct CircleLayer {
-    circles: Buffer<Circle>,
+impl HitShape for Circle {
+    fn bounds(&self) -> [f32; 4] {
+        let [x, y] = self.position;
+        [x - self.radius, y - self.radius, x + self.radius, y + self.radius]
+    }
+
+    fn contains(&self, point: [f32; 2]) -> bool {
+        let (dx, dy) = (point[0] - self.position[0], point[1] - self.position[1]);
+        dx * dx + dy * dy <= self.radius * self.radius
+    }
+}
+
+/// A ring, arc or pie slice of a circle. Angles are in radians,
+/// counterclockwise from the positive x axis.
+#[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CircleArc {
+    pub position: [f32; 2],
+    pub radius: f32,
+
+    /// Zero for a disc or pie slice, otherwise the radius of the hole in a
+    /// ring.
+    pub inner_radius: f32,
+
+    /// The arc covers the angles from `start_angle` to `end_angle`, e.g.
+    /// `0` to `TAU` for a full ring.
+    pub start_angle: f32,
+    pub end_angle: f32,
+
+    /// If positive, only the outline of the shape is drawn, this wide and
+    /// inside its edges.
+    pub stroke_width: f32,
+    pub color: Color,
+}
+
+impl CircleArc {
+    /// Signed distance from `point` to the shape's edge, negative inside.
+    /// This mirrors the fragment shader.
+    fn distance(&self, point: [f32; 2]) -> f32 {
+        let (dx, dy) = (point[0] - self.position[0], point[1] - self.position[1]);
+        let r = dx.hypot(dy);
+        let mut d = (r - self.radius).max(self.inner_radius - r);
+
+        let half_angle = ((self.end_angle - self.start_angle).abs() / 2.).min(std::f32::consts::PI);
+        let (sin, cos) = half_angle.sin_cos();
+        if cos > -0.9999 {
+            // Rotate the middle of the arc onto +y.
+            let rotation = std::f32::consts::FRAC_PI_2 - (self.start_angle + self.end_angle) / 2.;
+            let (rs, rc) = rotation.sin_cos();
+            let (px, py) = ((rc * dx - rs * dy).abs(), rs * dx + rc * dy);
+
+            let along = (px * sin + py * cos).max(0.);
+            let wedge = (px - sin * along).hypot(py - cos * along);
+            d = d.max(if cos * px - sin * py < 0. { -wedge } else { wedge });
+        }
+
+        if self.stroke_width > 0. {
+            d = (d + self.stroke_width / 2.).abs() - self.stroke_width / 2.;
+        }
+
+        d
+    }
+}
+
+impl HitShape for CircleArc {
+    fn bounds(&self) -> [f32; 4] {
+        let [x, y] = self.position;
+        [x - self.radius, y - self.radius, x + self.radius, y + self.radius]
+    }
+
+    fn contains(&self, point: [f32; 2]) -> bool {
+        self.distance(point) <= 0.
+    }
+}
+
+/// An instance type that a `CircleLayer` can draw: `Circle`, or `CircleArc`
+/// for rings, arcs and pie slices.
+pub trait CircleInstance: Attribute + InstanceShaders {}
+
+impl InstanceShaders for Circle {
+    const VERTEX_SHADER: &'static str = include_str!("shader.vert");
+    const FRAGMENT_SHADER: &'static str = include_str!("shader.frag");
+    const LABEL: &'static str = "circles";
+}
+
+impl CircleInstance for Circle {}
+
+impl InstanceShaders for CircleArc {
+    const VERTEX_SHADER: &'static str = include_str!("arc.vert");
+    const FRAGMENT_SHADER: &'static str = include_str!("arc.frag");
+    const LABEL: &'static str = "circle arcs";
+}
+
+impl CircleInstance for CircleArc {}
+
+pub struct CircleLayer<T: CircleInstance = Circle> {
+    circles: Buffer<T>,
     positions: Buffer<RelativePosition>,
-    program: Program<RelativePosition, Circle>,
-    transform: Uniform<[[f32; 4]; 4]>,
+    program: Program<RelativePosition, T>,
+    picking_program: Program<RelativePosition, T>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+    hit_index: HitIndex<T>,
 }
 
 impl Default for CircleLayer {
@@ -30,48 +125,122 @@ impl Default for CircleLayer {
     }
 }
 
+impl Default for CircleLayer<CircleArc> {
+    fn default() -> Self {
+        CircleLayer::arcs()
+    }
+}
+
 impl CircleLayer {
+    /// Create a layer with its own identity transform.
     pub fn new() -> Self {
-        Self::new_transform(Uniform::identity())
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
     }
 
+    /// Create a layer with its own transform.
     pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
-        let program = Program::new(
-            include_str!("shader.vert"),
-            include_str!("shader.frag"),
+        Self::new_impl(Some(transform))
+    }
+
+    /// Return the index of the topmost circle under `point`, given in clip
+    /// space. The inverse of the layer's transform is applied to the point;
+    /// for layers created with `new_global`, that is the `u_transform`
+    /// supplied by `uniforms`, usually the `Renderer`.
+    pub fn hit_test(&self, uniforms: &impl UniformSource, point: [f32; 2]) -> Option<usize> {
+        let point = to_data_space(&self.transform, uniforms, point)?;
+        self.hit_index.hit_test(&self.circles, point)
+    }
+}
+
+impl CircleLayer<CircleArc> {
+    /// Create a layer of `CircleArc`s with its own identity transform.
+    pub fn arcs() -> Self {
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer of `CircleArc`s that uses the renderer's global
+    /// `u_transform`, like `CircleLayer::new_global`.
+    pub fn arcs_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer of `CircleArc`s with its own transform, which takes
+    /// precedence over the renderer's global `u_transform`.
+    pub fn arcs_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(Some(transform))
+    }
+
+    /// Return the index of the topmost arc under `point`, given in clip
+    /// space, as for `CircleLayer::hit_test`. Only the drawn part of a
+    /// stroked arc counts.
+    pub fn hit_test(&self, uniforms: &impl UniformSource, point: [f32; 2]) -> Option<usize> {
+        let point = to_data_space(&self.transform, uniforms, point)?;
+        self.hit_index.hit_test(&self.circles, point)
+    }
+}
+
+impl<T: CircleInstance> CircleLayer<T> {
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let program = layer_program(
+            T::VERTEX_SHADER,
+            T::FRAGMENT_SHADER,
+            T::LABEL,
             DrawMode::TriangleStrip,
-        )
-        .with_state(StateDescriptor {
-            blend_func: Some(BlendFunction {
-                source_factor: BlendingFactorSrc::One,
-                dst_factor: BlendingFactorDest::OneMinusSrcAlpha,
-                ..Default::default()
-            }),
-            ..Default::default()
-        })
-        .with_uniform("u_transform", transform.clone());
+            &transform,
+        );
 
         CircleLayer {
             circles: Buffer::new_empty(BufferUsageHint::DynamicDraw),
             positions: Buffer::new(identity_quad(), BufferUsageHint::StaticDraw),
             program,
+            picking_program: picking_program(
+                T::VERTEX_SHADER,
+                T::FRAGMENT_SHADER,
+                T::LABEL,
+                &transform,
+            ),
             transform,
+            hit_index: HitIndex::default(),
         }
     }
 
-    pub fn buffer(&self) -> Buffer<Circle> {
+    pub fn buffer(&self) -> Buffer<T> {
         self.circles.clone()
     }
 
-    pub fn transform(&self) -> Uniform<[[f32; 4]; 4]> {
+    /// Copy the circles currently in the layer's buffer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<T> {
+        self.circles.data()
+    }
+
+    /// Replace the circles in the layer's buffer with previously exported ones.
+    pub fn import(&self, data: Vec<T>) {
+        self.circles.set_data(data);
+    }
+
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
         self.transform.clone()
     }
 }
 
-impl Drawable for CircleLayer {
+impl<T: CircleInstance> Drawable for CircleLayer<T> {
     fn draw(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
         renderer.render_instanced(&mut self.program, &self.positions, &self.circles)?;
 
         Ok(())
     }
 }
+
+impl<T: CircleInstance> Pickable for CircleLayer<T> {
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.picking_program, &self.positions, &self.circles)?;
+
+        Ok(())
+    }
+}
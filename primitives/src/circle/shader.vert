@@ -6,6 +6,9 @@ in float radius;
 in vec2 relative_position;
 
 flat out uint v_color;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
 out vec2 v_edge;
 out vec2 v_coord;
 
@@ -34,5 +37,8 @@ void main() {
     gl_Position = vec4(position + radius * relative_position, 0., 1.) * u_transform;
 
     v_color = color;
-}
-	
\ No newline at end of file
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
+}
\ No newline at end of file
@@ -5,7 +5,7 @@ use limelight::{
 use palette::{Srgb, Srgba};
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Zeroable, bytemuck::Pod)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Zeroable, bytemuck::Pod)]
 pub struct Color(pub u32);
 
 impl Color {
@@ -39,3 +39,42 @@ impl From<Srgba<u8>> for Color {
         ]))
     }
 }
+
+/// Colors are serialized as `#rrggbbaa` hex strings. When deserializing, the
+/// alpha component may be left out, as in `#rrggbb`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let [r, g, b, a]: [u8; 4] = bytemuck::cast(self.0);
+        serializer.serialize_str(&format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        let digits = hex.strip_prefix('#').unwrap_or(&hex);
+
+        // `from_str_radix` alone would also accept a sign, as in `+f`.
+        let channel = |i: usize| {
+            digits
+                .get(2 * i..2 * i + 2)
+                .filter(|c| c.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        };
+        let parsed: Option<Vec<u8>> = match digits.len() {
+            6 => (0..3).map(channel).chain([Some(0xff)]).collect(),
+            8 => (0..4).map(channel).collect(),
+            _ => None,
+        };
+
+        match parsed {
+            Some(bytes) => Ok(Color(bytemuck::cast([bytes[0], bytes[1], bytes[2], bytes[3]]))),
+            None => Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(&hex),
+                &"a color like #rrggbb or #rrggbbaa",
+            )),
+        }
+    }
+}
@@ -1,4 +1,113 @@
-use limelight::attribute;
+use limelight::{
+    attribute,
+    picking::picking_source,
+    shadow_gpu::UniformValue,
+    state::{
+        blending::{BlendFunction, BlendingFactorDest, BlendingFactorSrc},
+        StateDescriptor,
+    },
+    Attribute, DrawMode, Program, Uniform, UniformSource,
+};
+
+pub(crate) mod sealed {
+    /// The shaders that draw an instance type, for layers that can draw
+    /// several, like `CircleLayer`.
+    pub trait InstanceShaders {
+        const VERTEX_SHADER: &'static str;
+        const FRAGMENT_SHADER: &'static str;
+        const LABEL: &'static str;
+    }
+}
+
+/// The units of a size, such as a polyline's width or a sprite's size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Units {
+    /// The same units as the positions, so that sizes scale with the
+    /// transform.
+    #[default]
+    World,
+    /// CSS pixels, independent of the transform.
+    Screen,
+}
+
+/// The transform a layer draws with: its own, or for layers without one, the
+/// global `u_transform` that `uniforms` supplies.
+pub(crate) fn layer_transform(
+    transform: &Option<Uniform<[[f32; 4]; 4]>>,
+    uniforms: &impl UniformSource,
+) -> [[f32; 4]; 4] {
+    if let Some(transform) = transform {
+        return transform.value();
+    }
+
+    match uniforms.uniform_value("u_transform") {
+        Some(UniformValue::Mat4(m)) => m,
+        _ => Uniform::<[[f32; 4]; 4]>::identity().value(),
+    }
+}
+
+/// Build a layer's program, which blends with premultiplied alpha.
+///
+/// A layer's own `transform` is set as the program's `u_transform`. Layers
+/// without one (those created with `new_global`) instead use the global
+/// `u_transform` registered with `Renderer::set_global_uniform`, so that
+/// several layers can share one.
+pub fn layer_program<T: Attribute, I: Attribute>(
+    vertex_shader: &str,
+    fragment_shader: &str,
+    label: &str,
+    draw_mode: DrawMode,
+    transform: &Option<Uniform<[[f32; 4]; 4]>>,
+) -> Program<T, I> {
+    let program = Program::new(vertex_shader, fragment_shader, draw_mode)
+        .with_label(label)
+        .with_state(StateDescriptor {
+            blend_func: Some(BlendFunction {
+                source_factor: BlendingFactorSrc::One,
+                dst_factor: BlendingFactorDest::OneMinusSrcAlpha,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+    match transform {
+        Some(transform) => program.with_uniform("u_transform", transform.clone()),
+        None => program,
+    }
+}
+
+/// Build the picking variant of a layer's program, which writes instance IDs
+/// instead of colors (see `limelight::picking`).
+pub fn picking_program<T: Attribute, I: Attribute>(
+    vertex_shader: &str,
+    fragment_shader: &str,
+    label: &str,
+    transform: &Option<Uniform<[[f32; 4]; 4]>>,
+) -> Program<T, I> {
+    picking_program_with_mode(vertex_shader, fragment_shader, label, transform, DrawMode::TriangleStrip)
+}
+
+/// Like `picking_program`, for layers that don't draw triangle strips.
+pub fn picking_program_with_mode<T: Attribute, I: Attribute>(
+    vertex_shader: &str,
+    fragment_shader: &str,
+    label: &str,
+    transform: &Option<Uniform<[[f32; 4]; 4]>>,
+    draw_mode: DrawMode,
+) -> Program<T, I> {
+    let program = Program::new(
+        &picking_source(vertex_shader),
+        &picking_source(fragment_shader),
+        draw_mode,
+    )
+    .with_label(&format!("{} (picking)", label));
+
+    match transform {
+        Some(transform) => program.with_uniform("u_transform", transform.clone()),
+        None => program,
+    }
+}
 
 #[attribute]
 pub struct RelativePosition {
@@ -1,18 +1,20 @@
-use crate::{color::Color, common::{RelativePosition, identity_quad}};
-use anyhow::Result;
+use crate::{color::Color, common::{layer_transform, layer_program, picking_program, RelativePosition, identity_quad}};
 use limelight::{
     attribute,
+    picking::Pickable,
     renderer::Drawable,
-    state::{
-        blending::{BlendFunction, BlendingFactorDest, BlendingFactorSrc},
-        StateDescriptor,
-    },
     webgl::types::{DataType, SizedDataType},
-    AsSizedDataType, Buffer, BufferUsageHint, DrawMode, Program, Uniform,
+    AsSizedDataType, Buffer, BufferUsageHint, DrawMode, Program, Result, Uniform, UniformSource,
 };
+use std::cell::RefCell;
+
+/// How far to either side of its location a hairline is drawn, in clip
+/// space (see the shader).
+const THICKNESS: f32 = 0.002;
 
 #[repr(u32)]
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Orientation {
     Horizontal = 0x0,
     Vertical = 0x1,
@@ -28,6 +30,7 @@ impl AsSizedDataType for Orientation {
 }
 
 #[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hairline {
     pub location: f32,
     pub color: Color,
@@ -44,43 +47,150 @@ pub struct HairlineLayer {
     lines: Buffer<Hairline>,
     positions: Buffer<RelativePosition>,
     program: Program<RelativePosition, Hairline>,
-    transform: Uniform<[[f32; 4]; 4]>,
+    picking_program: Program<RelativePosition, Hairline>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+
+    /// How far from a hairline's location, in clip space, `hit_test` finds
+    /// it.
+    hit_tolerance: f32,
+    hit_index: RefCell<Option<HairlineIndex>>,
+}
+
+/// Hairline locations sorted by orientation, rebuilt the first time the
+/// layer is hit tested after its buffer changes.
+struct HairlineIndex {
+    version: u64,
+
+    /// `(location, index)` pairs, sorted by location.
+    horizontal: Vec<(f32, usize)>,
+    vertical: Vec<(f32, usize)>,
+}
+
+impl HairlineIndex {
+    fn new(version: u64, lines: &[Hairline]) -> Self {
+        let mut horizontal = Vec::new();
+        let mut vertical = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            match line.orientation {
+                Orientation::Horizontal => horizontal.push((line.location, index)),
+                Orientation::Vertical => vertical.push((line.location, index)),
+            }
+        }
+
+        horizontal.sort_by(|a, b| a.0.total_cmp(&b.0));
+        vertical.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        HairlineIndex {
+            version,
+            horizontal,
+            vertical,
+        }
+    }
+}
+
+/// The highest index of the lines whose location is in `[min, max]`.
+fn topmost_in_range(lines: &[(f32, usize)], min: f32, max: f32) -> Option<usize> {
+    let start = lines.partition_point(|(location, _)| *location < min);
+    let end = lines.partition_point(|(location, _)| *location <= max);
+    lines[start..end].iter().map(|(_, index)| *index).max()
 }
 
 impl HairlineLayer {
+    /// Create a layer with its own identity transform.
     pub fn new() -> Self {
-        Self::new_transform(Uniform::identity())
+        Self::new_impl(Some(Uniform::identity()))
     }
 
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer with its own transform.
     pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
-        let program = Program::new(
+        Self::new_impl(Some(transform))
+    }
+
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let program = layer_program(
             include_str!("shader.vert"),
             include_str!("shader.frag"),
+            "hairlines",
             DrawMode::TriangleStrip,
-        )
-        .with_state(StateDescriptor {
-            blend_func: Some(BlendFunction {
-                source_factor: BlendingFactorSrc::One,
-                dst_factor: BlendingFactorDest::OneMinusSrcAlpha,
-                ..Default::default()
-            }),
-            ..Default::default()
-        })
-        .with_uniform("u_transform", transform.clone());
+            &transform,
+        );
 
         HairlineLayer {
             lines: Buffer::new_empty(BufferUsageHint::DynamicDraw),
             positions: Buffer::new(identity_quad(), BufferUsageHint::StaticDraw),
             program,
+            picking_program: picking_program(
+                include_str!("shader.vert"),
+                include_str!("shader.frag"),
+                "hairlines",
+                &transform,
+            ),
             transform,
+            hit_tolerance: THICKNESS,
+            hit_index: RefCell::new(None),
         }
     }
 
+    /// Set how far from a hairline, in clip space, `hit_test` finds it. It
+    /// defaults to the drawn half-width of 0.002; a larger tolerance is
+    /// usually wanted for hovering.
+    pub fn with_hit_tolerance(mut self, tolerance: f32) -> Self {
+        self.hit_tolerance = tolerance;
+        self
+    }
+
     pub fn buffer(&self) -> Buffer<Hairline> {
         self.lines.clone()
     }
 
-    pub fn transform(&self) -> Uniform<[[f32; 4]; 4]> {
+    /// Copy the hairlines currently in the layer's buffer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<Hairline> {
+        self.lines.data()
+    }
+
+    /// Replace the hairlines in the layer's buffer with previously exported ones.
+    pub fn import(&self, data: Vec<Hairline>) {
+        self.lines.set_data(data);
+    }
+
+    /// Return the index of the topmost hairline within the layer's hit
+    /// tolerance of `point`, given in clip space. For layers created with
+    /// `new_global`, the transform is the `u_transform` supplied by
+    /// `uniforms`, usually the `Renderer`.
+    pub fn hit_test(&self, uniforms: &impl UniformSource, point: [f32; 2]) -> Option<usize> {
+        let mut index = self.hit_index.borrow_mut();
+        if index.as_ref().map(|i| i.version) != Some(self.lines.version()) {
+            *index = Some(HairlineIndex::new(self.lines.version(), &self.lines.data()));
+        }
+        let index = index.as_ref()?;
+
+        let m = layer_transform(&self.transform, uniforms);
+        let tolerance = self.hit_tolerance;
+
+        // The shader transforms `(location, location, 0, 1)`, so the clip
+        // position of a line along axis `i` is `location * scale + offset`.
+        let hit = |lines: &[(f32, usize)], i: usize| {
+            let scale = m[i][0] + m[i][1];
+            let offset = m[i][3];
+            if scale == 0. {
+                return None;
+            }
+
+            let a = (point[i] - tolerance - offset) / scale;
+            let b = (point[i] + tolerance - offset) / scale;
+            topmost_in_range(lines, a.min(b), a.max(b))
+        };
+
+        hit(&index.horizontal, 1).max(hit(&index.vertical, 0))
+    }
+
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
         self.transform.clone()
     }
 }
@@ -92,3 +202,11 @@ impl Drawable for HairlineLayer {
         Ok(())
     }
 }
+
+impl Pickable for HairlineLayer {
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.picking_program, &self.positions, &self.lines)?;
+
+        Ok(())
+    }
+}
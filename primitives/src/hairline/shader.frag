@@ -3,13 +3,23 @@
 precision highp float;
 
 flat in uint v_color;
+#ifdef PICKING
+flat in uint v_instance;
+uniform uint u_pick_id;
+out uvec4 f_id;
+#else
 out vec4 f_color;
+#endif
 
 void main() {
     float alpha = float((v_color & 0xFF000000u) >> 24) / 255.;
+#ifdef PICKING
+    f_id = uvec4(u_pick_id, v_instance, 0u, 0u);
+#else
     f_color = vec4(
         alpha * float((v_color & 0x000000FFu)) / 255.,
         alpha * float((v_color & 0x0000FF00u) >> 8) / 255.,
         alpha * float((v_color & 0x00FF0000u) >> 16) / 255.,
         alpha);
+#endif
 }
\ No newline at end of file
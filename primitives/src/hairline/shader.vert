@@ -6,6 +6,9 @@ in uint color;
 in vec2 relative_position;
 
 flat out uint v_color;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
 uniform mat4 u_transform;
 
 const float THICKNESS = 0.002;
@@ -32,4 +35,8 @@ void main() {
     }
 
     v_color = color;
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
 }
\ No newline at end of file
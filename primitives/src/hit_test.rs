@@ -0,0 +1,149 @@
+use crate::common::layer_transform;
+use limelight::{Attribute, Buffer, Uniform, UniformSource};
+use std::cell::RefCell;
+
+/// The largest number of grid cells along each axis of a `HitIndex`.
+const MAX_GRID_SIZE: usize = 256;
+
+/// Map a point from clip space back into a layer's data space by inverting
+/// the 2D part of the transform it draws with (see `layer_transform`).
+/// Returns `None` if the transform isn't invertible.
+pub(crate) fn to_data_space(
+    transform: &Option<Uniform<[[f32; 4]; 4]>>,
+    uniforms: &impl UniformSource,
+    point: [f32; 2],
+) -> Option<[f32; 2]> {
+    let m = layer_transform(transform, uniforms);
+
+    // Each inner array is a row, so clip = M * (x, y, 0, 1).
+    let [a, b, tx] = [m[0][0], m[0][1], m[0][3]];
+    let [c, d, ty] = [m[1][0], m[1][1], m[1][3]];
+
+    let det = a * d - b * c;
+    if det == 0. {
+        return None;
+    }
+
+    let (x, y) = (point[0] - tx, point[1] - ty);
+    Some([(d * x - b * y) / det, (a * y - c * x) / det])
+}
+
+/// A shape that can be found by `HitIndex`.
+pub(crate) trait HitShape {
+    /// The bounding box as `[min_x, min_y, max_x, max_y]`, in data space.
+    fn bounds(&self) -> [f32; 4];
+
+    fn contains(&self, point: [f32; 2]) -> bool;
+}
+
+struct Grid {
+    min: [f32; 2],
+    cell_size: [f32; 2],
+    size: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl Grid {
+    fn new(bounds: &[[f32; 4]]) -> Self {
+        let mut min = [f32::INFINITY; 2];
+        let mut max = [f32::NEG_INFINITY; 2];
+        for b in bounds {
+            min = [min[0].min(b[0]), min[1].min(b[1])];
+            max = [max[0].max(b[2]), max[1].max(b[3])];
+        }
+
+        // Aim for about one shape per cell.
+        let size = ((bounds.len() as f32).sqrt().ceil() as usize).clamp(1, MAX_GRID_SIZE);
+        let cell_size = [0, 1].map(|i| {
+            let extent = (max[i] - min[i]) / size as f32;
+            if extent > 0. && extent.is_finite() {
+                extent
+            } else {
+                1.
+            }
+        });
+
+        let mut grid = Grid {
+            min,
+            cell_size,
+            size,
+            cells: vec![Vec::new(); size * size],
+        };
+
+        for (index, b) in bounds.iter().enumerate() {
+            let (x0, y0) = grid.cell([b[0], b[1]]);
+            let (x1, y1) = grid.cell([b[2], b[3]]);
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    grid.cells[y * size + x].push(index);
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// The cell containing `point`, clamped to the grid.
+    fn cell(&self, point: [f32; 2]) -> (usize, usize) {
+        let [x, y] = [0, 1].map(|i| {
+            let cell = ((point[i] - self.min[i]) / self.cell_size[i]).floor();
+            (cell.max(0.) as usize).min(self.size - 1)
+        });
+
+        (x, y)
+    }
+
+    /// Indices of the shapes whose bounds overlap the cell containing
+    /// `point`, in ascending order.
+    fn candidates(&self, point: [f32; 2]) -> &[usize] {
+        let (x, y) = self.cell(point);
+        &self.cells[y * self.size + x]
+    }
+}
+
+struct HitIndexCache<T> {
+    version: u64,
+    shapes: Vec<T>,
+    grid: Grid,
+}
+
+/// A uniform grid over the shapes in a layer's buffer, rebuilt the first
+/// time it is queried after the buffer changes.
+pub(crate) struct HitIndex<T> {
+    cache: RefCell<Option<HitIndexCache<T>>>,
+}
+
+impl<T> Default for HitIndex<T> {
+    fn default() -> Self {
+        HitIndex {
+            cache: RefCell::new(None),
+        }
+    }
+}
+
+impl<T: Attribute + HitShape> HitIndex<T> {
+    /// Return the index of the topmost (i.e. last drawn) shape containing
+    /// `point`, given in data space.
+    pub fn hit_test(&self, buffer: &Buffer<T>, point: [f32; 2]) -> Option<usize> {
+        let mut cache = self.cache.borrow_mut();
+
+        if cache.as_ref().map(|c| c.version) != Some(buffer.version()) {
+            let shapes = buffer.data();
+            let bounds: Vec<[f32; 4]> = shapes.iter().map(HitShape::bounds).collect();
+            *cache = Some(HitIndexCache {
+                version: buffer.version(),
+                grid: Grid::new(&bounds),
+                shapes,
+            });
+        }
+
+        let cache = cache.as_ref()?;
+        cache
+            .grid
+            .candidates(point)
+            .iter()
+            .rev()
+            .copied()
+            .find(|&index| cache.shapes[index].contains(point))
+    }
+}
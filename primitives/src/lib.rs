@@ -4,13 +4,31 @@ mod circle;
 mod color;
 mod common;
 mod hairline;
+mod hit_test;
 mod line;
+mod marker;
+mod packer;
+mod path;
+mod polygon;
+mod polyline;
 mod rect;
+mod sprite;
+mod text;
 mod line3d;
 
-pub use circle::{Circle, CircleLayer};
+pub use circle::{Circle, CircleArc, CircleInstance, CircleLayer};
 pub use color::Color;
+pub use common::Units;
 pub use hairline::{Hairline, HairlineLayer, Orientation};
-pub use line::{Line, LineLayer};
-pub use rect::{Rect, RectLayer};
-pub use line3d::{Line3D, Line3DLayer};
+pub use line::{DashedLine, Line, LineInstance, LineLayer};
+pub use marker::{Marker, MarkerLayer, MarkerShape};
+pub use path::{Path, PathCommand, PathLayer, Stroke, Subpath};
+pub use polygon::{Polygon, PolygonLayer};
+pub use polyline::{LineCap, LineJoin, Polyline, PolylineLayer};
+pub use rect::{Rect, RectInstance, RectLayer, RoundedRect};
+pub use sprite::{Sprite, SpriteAtlas, SpriteLayer};
+pub use line3d::{DashedLine3D, Line3D, Line3DInstance, Line3DLayer};
+pub use text::{
+    Anchor, CanvasRasterizer, FontMetrics, GlyphRasterizer, HorizontalAnchor, RasterizedGlyph, Text,
+    TextLayer, TextMetrics, VerticalAnchor,
+};
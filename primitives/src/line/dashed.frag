@@ -0,0 +1,53 @@
+#version 300 es
+
+precision highp float;
+
+flat in uint v_color;
+in vec2 v_edge;
+in float v_dash;
+flat in vec3 v_dash_pattern;
+
+#ifdef PICKING
+flat in uint v_instance;
+uniform uint u_pick_id;
+out uvec4 f_id;
+#else
+out vec4 f_color;
+#endif
+
+// Coverage of the dash pattern at `position` pixels along the line.
+float dash_coverage(float position) {
+    float delta = fwidth(position);
+    float dash_length = v_dash_pattern.x;
+    if (dash_length <= 0.) {
+        return 1.;
+    }
+
+    float t = mod(position + v_dash_pattern.z, dash_length + v_dash_pattern.y);
+    return clamp(min(t, dash_length - t) / delta + 0.5, 0., 1.);
+}
+
+void main() {
+    float dx = fwidth(v_edge.x);
+    float dy = fwidth(v_edge.y);
+
+    float xcov = min(clamp(0., 1., v_edge.x / dx), clamp(0., 1., (1. - v_edge.x) / dx));
+    float ycov = min(clamp(0., 1., v_edge.y / dy), clamp(0., 1., (1. - v_edge.y) / dy));
+    float alpha = xcov * ycov;
+
+#ifdef PICKING
+    // Gaps between dashes are still pickable, like in `hit_test`.
+    f_id = uvec4(u_pick_id, v_instance, 0u, 0u);
+#else
+    float dash = dash_coverage(v_dash);
+    if (dash < 0.01) {
+        discard;
+    }
+
+    f_color = vec4(
+        float((v_color & 0x000000FFu)) / 255.,
+        float((v_color & 0x0000FF00u) >> 8) / 255.,
+        float((v_color & 0x00FF0000u) >> 16) / 255.,
+        float((v_color & 0xFF000000u) >> 24) / 255.) * dash;
+#endif
+}
\ No newline at end of file
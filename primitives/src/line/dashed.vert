@@ -0,0 +1,44 @@
+#version 300 es
+
+in vec2 start;
+in vec2 end;
+in uint color;
+in float width;
+in float dash_length;
+in float dash_gap;
+in float dash_phase;
+in vec2 line_position;
+in vec2 line_edge;
+uniform mat4 u_transform;
+uniform vec2 u_resolution;
+
+flat out uint v_color;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
+out vec2 v_edge;
+// Distance along the line in CSS pixels.
+out float v_dash;
+flat out vec3 v_dash_pattern;
+
+void main() {
+    vec2 line = normalize(end - start);
+    vec2 perp = vec2(line.y, -line.x);
+
+    v_edge = line_edge;
+    vec2 pos = (line_position.x * end) + ((1.-line_position.x) * start) + perp * width * line_position.y;
+
+    gl_Position = vec4(pos, 0., 1.) * u_transform;
+
+    vec2 screen_line = ((vec4(end, 0., 1.) - vec4(start, 0., 1.)) * u_transform).xy * u_resolution / 2.;
+    v_dash = line_position.x * length(screen_line);
+    v_edge = line_edge;
+
+    v_color = color;
+
+    v_dash_pattern = vec3(dash_length, dash_gap, dash_phase);
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
+}
\ No newline at end of file
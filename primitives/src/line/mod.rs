@@ -1,16 +1,14 @@
-use crate::{color::Color, common::{LinePosition, identity_line}};
-use anyhow::Result;
+use crate::{color::Color, hit_test::{to_data_space, HitIndex, HitShape}, common::{sealed::InstanceShaders, layer_program, picking_program, LinePosition, identity_line}};
 use limelight::{
     attribute,
+    Attribute,
+    picking::Pickable,
     renderer::Drawable,
-    state::{
-        blending::{BlendFunction, BlendingFactorDest, BlendingFactorSrc},
-        StateDescriptor,
-    },
-    Buffer, BufferUsageHint, DrawMode, Program, Uniform,
+    Buffer, BufferUsageHint, DrawMode, Program, Result, Uniform, UniformSource,
 };
 
 #[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line {
     pub start: [f32; 2],
     pub end: [f32; 2],
@@ -18,11 +16,99 @@ pub struct Line {
     pub color: Color,
 }
 
-pub struct LineLayer {
-    lines: Buffer<Line>,
+impl HitShape for Line {
+    fn bounds(&self) -> [f32; 4] {
+        segment_bounds(self.start, self.end, self.width)
+    }
+
+    /// Lines are drawn `width` to either side of the segment, with square
+    /// ends that don't extend past its endpoints.
+    fn contains(&self, point: [f32; 2]) -> bool {
+        segment_contains(self.start, self.end, self.width, point)
+    }
+}
+
+/// A straight line drawn as a repeating pattern of dashes and gaps.
+#[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DashedLine {
+    pub start: [f32; 2],
+    pub end: [f32; 2],
+    pub width: f32,
+    pub color: Color,
+
+    /// Length of each dash in CSS pixels, or zero for a solid line.
+    pub dash_length: f32,
+
+    /// Length of the gaps between dashes, in CSS pixels.
+    pub dash_gap: f32,
+
+    /// How far into the dash pattern the line starts, in CSS pixels.
+    pub dash_phase: f32,
+}
+
+impl HitShape for DashedLine {
+    fn bounds(&self) -> [f32; 4] {
+        segment_bounds(self.start, self.end, self.width)
+    }
+
+    /// As for `Line`; gaps between dashes count as part of the line.
+    fn contains(&self, point: [f32; 2]) -> bool {
+        segment_contains(self.start, self.end, self.width, point)
+    }
+}
+
+fn segment_bounds(start: [f32; 2], end: [f32; 2], width: f32) -> [f32; 4] {
+    let [x0, y0] = start;
+    let [x1, y1] = end;
+    [
+        x0.min(x1) - width,
+        y0.min(y1) - width,
+        x0.max(x1) + width,
+        y0.max(y1) + width,
+    ]
+}
+
+fn segment_contains(start: [f32; 2], end: [f32; 2], width: f32, point: [f32; 2]) -> bool {
+    let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+    let (px, py) = (point[0] - start[0], point[1] - start[1]);
+
+    let length_squared = dx * dx + dy * dy;
+    if length_squared == 0. {
+        return false;
+    }
+
+    let along = (px * dx + py * dy) / length_squared;
+    let across = (px * dy - py * dx).abs() / length_squared.sqrt();
+    (0. ..=1.).contains(&along) && across <= width
+}
+
+/// An instance type that a `LineLayer` can draw: `Line`, or `DashedLine`.
+pub trait LineInstance: Attribute + InstanceShaders {}
+
+impl InstanceShaders for Line {
+    const VERTEX_SHADER: &'static str = include_str!("shader.vert");
+    const FRAGMENT_SHADER: &'static str = include_str!("shader.frag");
+    const LABEL: &'static str = "lines";
+}
+
+impl LineInstance for Line {}
+
+impl InstanceShaders for DashedLine {
+    const VERTEX_SHADER: &'static str = include_str!("dashed.vert");
+    const FRAGMENT_SHADER: &'static str = include_str!("dashed.frag");
+    const LABEL: &'static str = "dashed lines";
+}
+
+impl LineInstance for DashedLine {}
+
+pub struct LineLayer<T: LineInstance = Line> {
+    lines: Buffer<T>,
     positions: Buffer<LinePosition>,
-    program: Program<LinePosition, Line>,
-    transform: Uniform<[[f32; 4]; 4]>,
+    program: Program<LinePosition, T>,
+    picking_program: Program<LinePosition, T>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+    hit_index: HitIndex<T>,
 }
 
 impl Default for LineLayer {
@@ -31,48 +117,121 @@ impl Default for LineLayer {
     }
 }
 
+impl Default for LineLayer<DashedLine> {
+    fn default() -> Self {
+        LineLayer::dashed()
+    }
+}
+
 impl LineLayer {
+    /// Create a layer with its own identity transform.
     pub fn new() -> Self {
-        Self::new_transform(Uniform::identity())
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
     }
 
+    /// Create a layer with its own transform.
     pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
-        let program = Program::new(
-            include_str!("shader.vert"),
-            include_str!("shader.frag"),
+        Self::new_impl(Some(transform))
+    }
+
+    /// Return the index of the topmost line under `point`, given in clip
+    /// space. The inverse of the layer's transform is applied to the point;
+    /// for layers created with `new_global`, that is the `u_transform`
+    /// supplied by `uniforms`, usually the `Renderer`.
+    pub fn hit_test(&self, uniforms: &impl UniformSource, point: [f32; 2]) -> Option<usize> {
+        let point = to_data_space(&self.transform, uniforms, point)?;
+        self.hit_index.hit_test(&self.lines, point)
+    }
+}
+
+impl LineLayer<DashedLine> {
+    /// Create a layer of `DashedLine`s with its own identity transform.
+    pub fn dashed() -> Self {
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer of `DashedLine`s that uses the renderer's global
+    /// `u_transform`, like `LineLayer::new_global`.
+    pub fn dashed_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer of `DashedLine`s with its own transform, which takes
+    /// precedence over the renderer's global `u_transform`.
+    pub fn dashed_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(Some(transform))
+    }
+
+    /// Return the index of the topmost dashed line under `point`, given in
+    /// clip space, as for `LineLayer::hit_test`.
+    pub fn hit_test(&self, uniforms: &impl UniformSource, point: [f32; 2]) -> Option<usize> {
+        let point = to_data_space(&self.transform, uniforms, point)?;
+        self.hit_index.hit_test(&self.lines, point)
+    }
+}
+
+impl<T: LineInstance> LineLayer<T> {
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let program = layer_program(
+            T::VERTEX_SHADER,
+            T::FRAGMENT_SHADER,
+            T::LABEL,
             DrawMode::TriangleStrip,
-        )
-        .with_state(StateDescriptor {
-            blend_func: Some(BlendFunction {
-                source_factor: BlendingFactorSrc::One,
-                dst_factor: BlendingFactorDest::OneMinusSrcAlpha,
-                ..Default::default()
-            }),
-            ..Default::default()
-        })
-        .with_uniform("u_transform", transform.clone());
+            &transform,
+        );
 
         LineLayer {
             lines: Buffer::new_empty(BufferUsageHint::DynamicDraw),
             positions: Buffer::new(identity_line(), BufferUsageHint::StaticDraw),
             program,
+            picking_program: picking_program(
+                T::VERTEX_SHADER,
+                T::FRAGMENT_SHADER,
+                T::LABEL,
+                &transform,
+            ),
             transform,
+            hit_index: HitIndex::default(),
         }
     }
 
-    pub fn transform(&self) -> Uniform<[[f32; 4]; 4]> {
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
         self.transform.clone()
     }
 
-    pub fn buffer(&self) -> Buffer<Line> {
+    pub fn buffer(&self) -> Buffer<T> {
         self.lines.clone()
     }
+
+    /// Copy the lines currently in the layer's buffer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<T> {
+        self.lines.data()
+    }
+
+    /// Replace the lines in the layer's buffer with previously exported ones.
+    pub fn import(&self, data: Vec<T>) {
+        self.lines.set_data(data);
+    }
 }
 
-impl Drawable for LineLayer {
+impl<T: LineInstance> Drawable for LineLayer<T> {
     fn draw(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
         renderer.render_instanced(&mut self.program, &self.positions, &self.lines)?;
 
         Ok(())
     }
 }
+
+impl<T: LineInstance> Pickable for LineLayer<T> {
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.picking_program, &self.positions, &self.lines)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+#version 300 es
+
+in vec3 start;
+in vec3 end;
+in uint color;
+in float width;
+in float dash_length;
+in float dash_gap;
+in float dash_phase;
+in vec2 line_position;
+in vec2 line_edge;
+uniform mat4 u_transform;
+uniform vec2 u_resolution;
+
+flat out uint v_color;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
+out vec2 v_edge;
+// Distance along the line in CSS pixels times w, and w, so that dividing
+// them undoes perspective-correct interpolation.
+out vec2 v_dash;
+flat out vec3 v_dash_pattern;
+
+void main() {
+    vec3 line = normalize(end - start);
+    // TODO: should find a perpendicular line in post-transform space instead of pre?
+    vec3 perp = vec3(line.y, -line.x, 0.);
+
+    v_edge = line_edge;
+    vec3 pos = (line_position.x * end) + ((1.-line_position.x) * start) + perp * width * line_position.y;
+
+    gl_Position = vec4(pos, 1.0) * u_transform;
+
+    vec4 clip_start = vec4(start, 1.) * u_transform;
+    vec4 clip_end = vec4(end, 1.) * u_transform;
+    vec2 screen_line = (clip_end.xy / clip_end.w - clip_start.xy / clip_start.w) * u_resolution / 2.;
+    v_dash = vec2(line_position.x * length(screen_line), 1.) * gl_Position.w;
+
+    v_color = color;
+
+    v_dash_pattern = vec3(dash_length, dash_gap, dash_phase);
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
+}
\ No newline at end of file
@@ -1,16 +1,14 @@
-use crate::{color::Color, common::{LinePosition, identity_line}};
-use anyhow::Result;
+use crate::{color::Color, common::{sealed::InstanceShaders, layer_program, picking_program, LinePosition, identity_line}};
 use limelight::{
     attribute,
+    Attribute,
+    picking::Pickable,
     renderer::Drawable,
-    state::{
-        blending::{BlendFunction, BlendingFactorDest, BlendingFactorSrc},
-        StateDescriptor,
-    },
-    Buffer, BufferUsageHint, DrawMode, Program, Uniform,
+    Buffer, BufferUsageHint, DrawMode, Program, Result, Uniform,
 };
 
 #[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Line3D {
     pub start: [f32; 3],
     pub end: [f32; 3],
@@ -18,11 +16,51 @@ pub struct Line3D {
     pub color: Color,
 }
 
-pub struct Line3DLayer {
-    lines: Buffer<Line3D>,
+/// A straight line in 3D drawn as a repeating pattern of dashes and gaps.
+#[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DashedLine3D {
+    pub start: [f32; 3],
+    pub end: [f32; 3],
+    pub width: f32,
+    pub color: Color,
+
+    /// Length of each dash in CSS pixels, or zero for a solid line.
+    pub dash_length: f32,
+
+    /// Length of the gaps between dashes, in CSS pixels.
+    pub dash_gap: f32,
+
+    /// How far into the dash pattern the line starts, in CSS pixels.
+    pub dash_phase: f32,
+}
+
+/// An instance type that a `Line3DLayer` can draw: `Line3D`, or
+/// `DashedLine3D`.
+pub trait Line3DInstance: Attribute + InstanceShaders {}
+
+impl InstanceShaders for Line3D {
+    const VERTEX_SHADER: &'static str = include_str!("shader.vert");
+    const FRAGMENT_SHADER: &'static str = include_str!("shader.frag");
+    const LABEL: &'static str = "lines3d";
+}
+
+impl Line3DInstance for Line3D {}
+
+impl InstanceShaders for DashedLine3D {
+    const VERTEX_SHADER: &'static str = include_str!("dashed.vert");
+    const FRAGMENT_SHADER: &'static str = include_str!("dashed.frag");
+    const LABEL: &'static str = "dashed lines3d";
+}
+
+impl Line3DInstance for DashedLine3D {}
+
+pub struct Line3DLayer<T: Line3DInstance = Line3D> {
+    lines: Buffer<T>,
     positions: Buffer<LinePosition>,
-    program: Program<LinePosition, Line3D>,
-    transform: Uniform<[[f32; 4]; 4]>,
+    program: Program<LinePosition, T>,
+    picking_program: Program<LinePosition, T>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
 }
 
 impl Default for Line3DLayer {
@@ -31,48 +69,104 @@ impl Default for Line3DLayer {
     }
 }
 
+impl Default for Line3DLayer<DashedLine3D> {
+    fn default() -> Self {
+        Line3DLayer::dashed()
+    }
+}
+
 impl Line3DLayer {
+    /// Create a layer with its own identity transform.
     pub fn new() -> Self {
-        Self::new_transform(Uniform::identity())
+        Self::new_impl(Some(Uniform::identity()))
     }
 
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer with its own transform.
     pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
-        let program = Program::new(
-            include_str!("shader.vert"),
-            include_str!("shader.frag"),
+        Self::new_impl(Some(transform))
+    }
+}
+
+impl Line3DLayer<DashedLine3D> {
+    /// Create a layer of `DashedLine3D`s with its own identity transform.
+    pub fn dashed() -> Self {
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer of `DashedLine3D`s that uses the renderer's global
+    /// `u_transform`, like `Line3DLayer::new_global`.
+    pub fn dashed_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer of `DashedLine3D`s with its own transform, which takes
+    /// precedence over the renderer's global `u_transform`.
+    pub fn dashed_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(Some(transform))
+    }
+}
+
+impl<T: Line3DInstance> Line3DLayer<T> {
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let program = layer_program(
+            T::VERTEX_SHADER,
+            T::FRAGMENT_SHADER,
+            T::LABEL,
             DrawMode::TriangleStrip,
-        )
-        .with_state(StateDescriptor {
-            blend_func: Some(BlendFunction {
-                source_factor: BlendingFactorSrc::One,
-                dst_factor: BlendingFactorDest::OneMinusSrcAlpha,
-                ..Default::default()
-            }),
-            ..Default::default()
-        })
-        .with_uniform("u_transform", transform.clone());
+            &transform,
+        );
 
         Line3DLayer {
             lines: Buffer::new_empty(BufferUsageHint::DynamicDraw),
             program,
+            picking_program: picking_program(
+                T::VERTEX_SHADER,
+                T::FRAGMENT_SHADER,
+                T::LABEL,
+                &transform,
+            ),
             transform,
             positions: Buffer::new(identity_line(), BufferUsageHint::StaticDraw),
         }
     }
 
-    pub fn transform(&self) -> Uniform<[[f32; 4]; 4]> {
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
         self.transform.clone()
     }
 
-    pub fn buffer(&self) -> Buffer<Line3D> {
+    pub fn buffer(&self) -> Buffer<T> {
         self.lines.clone()
     }
+
+    /// Copy the lines currently in the layer's buffer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<T> {
+        self.lines.data()
+    }
+
+    /// Replace the lines in the layer's buffer with previously exported ones.
+    pub fn import(&self, data: Vec<T>) {
+        self.lines.set_data(data);
+    }
 }
 
-impl Drawable for Line3DLayer {
+impl<T: Line3DInstance> Drawable for Line3DLayer<T> {
     fn draw(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
         renderer.render_instanced(&mut self.program, &self.positions, &self.lines)?;
 
         Ok(())
     }
 }
+
+impl<T: Line3DInstance> Pickable for Line3DLayer<T> {
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.picking_program, &self.positions, &self.lines)?;
+
+        Ok(())
+    }
+}
@@ -9,6 +9,9 @@ in vec2 line_edge;
 uniform mat4 u_transform;
 
 flat out uint v_color;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
 out vec2 v_edge;
 
 void main() {
@@ -22,4 +25,8 @@ void main() {
     gl_Position = vec4(pos, 1.0) * u_transform;
 
     v_color = color;
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
 }
\ No newline at end of file
@@ -0,0 +1,162 @@
+use crate::{color::Color, common::{layer_program, picking_program, RectPosition, identity_rect}};
+use limelight::{
+    attribute,
+    picking::Pickable,
+    renderer::Drawable,
+    Buffer, BufferUsageHint, DrawMode, Program, Result, Uniform,
+};
+use std::cell::RefCell;
+
+/// The shape of a marker. Each fits in a circle of the marker's size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MarkerShape {
+    #[default]
+    Circle,
+    Square,
+    Diamond,
+    /// An equilateral triangle pointing up.
+    Triangle,
+    /// A diagonal cross, like an x.
+    Cross,
+    Plus,
+    /// A five-pointed star pointing up.
+    Star,
+}
+
+/// A point marker for scatter plots, sized in CSS pixels independent of
+/// the transform.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Marker {
+    pub position: [f32; 2],
+
+    /// The diameter of the circle the shape fits in, in CSS pixels.
+    pub size: f32,
+    pub shape: MarkerShape,
+    pub color: Color,
+
+    /// If set, an outline `outline_width` CSS pixels wide is drawn inside
+    /// the shape's edge in this color.
+    pub outline_color: Option<Color>,
+    pub outline_width: f32,
+}
+
+#[attribute]
+struct MarkerInstance {
+    position: [f32; 2],
+    size: f32,
+
+    /// The index of the shape in `MarkerShape`.
+    shape: u32,
+    color: Color,
+    outline_color: Color,
+
+    /// Zero when there is no outline.
+    outline_width: f32,
+}
+
+impl From<&Marker> for MarkerInstance {
+    fn from(marker: &Marker) -> Self {
+        MarkerInstance {
+            position: marker.position,
+            size: marker.size,
+            shape: marker.shape as u32,
+            color: marker.color,
+            outline_color: marker.outline_color.unwrap_or_default(),
+            outline_width: if marker.outline_color.is_some() {
+                marker.outline_width
+            } else {
+                0.
+            },
+        }
+    }
+}
+
+pub struct MarkerLayer {
+    markers: RefCell<Vec<Marker>>,
+    instances: Buffer<MarkerInstance>,
+    positions: Buffer<RectPosition>,
+    program: Program<RectPosition, MarkerInstance>,
+    picking_program: Program<RectPosition, MarkerInstance>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+}
+
+impl Default for MarkerLayer {
+    fn default() -> Self {
+        MarkerLayer::new()
+    }
+}
+
+impl MarkerLayer {
+    /// Create a layer with its own identity transform.
+    pub fn new() -> Self {
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer with its own transform.
+    pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(Some(transform))
+    }
+
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let program = layer_program(
+            include_str!("shader.vert"),
+            include_str!("shader.frag"),
+            "markers",
+            DrawMode::TriangleStrip,
+            &transform,
+        );
+
+        MarkerLayer {
+            markers: RefCell::new(Vec::new()),
+            instances: Buffer::new_empty(BufferUsageHint::DynamicDraw),
+            positions: Buffer::new(identity_rect(), BufferUsageHint::StaticDraw),
+            program,
+            picking_program: picking_program(
+                include_str!("shader.vert"),
+                include_str!("shader.frag"),
+                "markers",
+                &transform,
+            ),
+            transform,
+        }
+    }
+
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
+        self.transform.clone()
+    }
+
+    /// Copy the markers currently in the layer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<Marker> {
+        self.markers.borrow().clone()
+    }
+
+    /// Replace the markers in the layer.
+    pub fn import(&self, data: Vec<Marker>) {
+        self.instances.set_data(data.iter().map(MarkerInstance::from).collect());
+        *self.markers.borrow_mut() = data;
+    }
+}
+
+impl Drawable for MarkerLayer {
+    fn draw(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.program, &self.positions, &self.instances)?;
+
+        Ok(())
+    }
+}
+
+impl Pickable for MarkerLayer {
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.picking_program, &self.positions, &self.instances)?;
+
+        Ok(())
+    }
+}
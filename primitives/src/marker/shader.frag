@@ -0,0 +1,116 @@
+#version 300 es
+
+precision highp float;
+
+flat in uint v_shape;
+flat in uint v_color;
+flat in uint v_outline_color;
+flat in float v_outline_width;
+flat in float v_radius;
+in vec2 v_coord;
+#ifdef PICKING
+flat in uint v_instance;
+uniform uint u_pick_id;
+out uvec4 f_id;
+#else
+out vec4 f_color;
+#endif
+
+// Shapes, in the order of `MarkerShape`.
+const uint CIRCLE = 0u;
+const uint SQUARE = 1u;
+const uint DIAMOND = 2u;
+const uint TRIANGLE = 3u;
+const uint CROSS = 4u;
+const uint PLUS = 5u;
+const uint STAR = 6u;
+
+vec4 unpack_color(uint color) {
+    return vec4(
+        float((color & 0x000000FFu)) / 255.,
+        float((color & 0x0000FF00u) >> 8) / 255.,
+        float((color & 0x00FF0000u) >> 16) / 255.,
+        float((color & 0xFF000000u) >> 24) / 255.);
+}
+
+float box_distance(vec2 p, vec2 half_size) {
+    vec2 q = abs(p) - half_size;
+    return length(max(q, 0.)) + min(max(q.x, q.y), 0.);
+}
+
+float plus_distance(vec2 p, float r) {
+    float arm = r / 4.;
+    return min(box_distance(p, vec2(r, arm)), box_distance(p, vec2(arm, r)));
+}
+
+// An equilateral triangle inscribed in a circle of radius `r`.
+float triangle_distance(vec2 p, float r) {
+    const float k = sqrt(3.);
+    float side = r * k / 2.;
+    p.x = abs(p.x) - side;
+    p.y = p.y + side / k;
+    if (p.x + k * p.y > 0.) {
+        p = vec2(p.x - k * p.y, -k * p.x - p.y) / 2.;
+    }
+    p.x -= clamp(p.x, -2. * side, 0.);
+    return -length(p) * sign(p.y);
+}
+
+// A five-pointed star with points on a circle of radius `r`.
+float star_distance(vec2 p, float r) {
+    const vec2 k1 = vec2(0.809016994375, -0.587785252292);
+    const vec2 k2 = vec2(-k1.x, k1.y);
+    const float inner = 0.45;
+
+    p.x = abs(p.x);
+    p -= 2. * max(dot(k1, p), 0.) * k1;
+    p -= 2. * max(dot(k2, p), 0.) * k2;
+    p.x = abs(p.x);
+    p.y -= r;
+
+    vec2 ba = inner * vec2(-k1.y, k1.x) - vec2(0., 1.);
+    float h = clamp(dot(p, ba) / dot(ba, ba), 0., r);
+    return length(p - ba * h) * sign(p.y * ba.x - p.x * ba.y);
+}
+
+// Signed distance to the edge of the marker in CSS pixels, negative inside.
+float marker_distance(vec2 p, float r) {
+    if (v_shape == SQUARE) {
+        return box_distance(p, vec2(r / sqrt(2.)));
+    } else if (v_shape == DIAMOND) {
+        return (abs(p.x) + abs(p.y) - r) / sqrt(2.);
+    } else if (v_shape == TRIANGLE) {
+        return triangle_distance(p, r);
+    } else if (v_shape == CROSS) {
+        return plus_distance(vec2(p.x + p.y, p.y - p.x) / sqrt(2.), r);
+    } else if (v_shape == PLUS) {
+        return plus_distance(p, r);
+    } else if (v_shape == STAR) {
+        return star_distance(p, r);
+    }
+
+    return length(p) - r;
+}
+
+void main() {
+    float d = marker_distance(v_coord, v_radius);
+    float delta = fwidth(d);
+
+    float alpha = 1.0 - smoothstep(-delta, 0., d);
+
+    if (alpha < 0.01) {
+        discard;
+    }
+
+#ifdef PICKING
+    f_id = uvec4(u_pick_id, v_instance, 0u, 0u);
+#else
+    vec4 color = unpack_color(v_color);
+    if (v_outline_width > 0.) {
+        float outline = smoothstep(-v_outline_width - delta, -v_outline_width, d);
+        color = mix(color, unpack_color(v_outline_color), outline);
+    }
+
+    f_color = color * alpha;
+#endif
+}
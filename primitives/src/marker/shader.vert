@@ -0,0 +1,42 @@
+#version 300 es
+
+in vec2 position;
+in float size;
+in uint shape;
+in uint color;
+in uint outline_color;
+in float outline_width;
+in vec2 rect_position;
+
+flat out uint v_shape;
+flat out uint v_color;
+flat out uint v_outline_color;
+flat out float v_outline_width;
+flat out float v_radius;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
+// Offset from the marker's position, in CSS pixels.
+out vec2 v_coord;
+
+uniform mat4 u_transform;
+uniform vec2 u_resolution;
+
+void main() {
+    // Leave a pixel around the shape for anti-aliasing.
+    vec2 corner = (rect_position - 0.5) * (size + 2.);
+
+    gl_Position = vec4(position, 0., 1.) * u_transform;
+    gl_Position.xy += corner * 2. / u_resolution * gl_Position.w;
+
+    v_coord = corner;
+    v_radius = size / 2.;
+    v_shape = shape;
+    v_color = color;
+    v_outline_color = outline_color;
+    v_outline_width = outline_width;
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
+}
@@ -0,0 +1,49 @@
+/// Packs rectangles into a fixed-size area in rows ("shelves"), each as tall
+/// as the tallest rectangle placed in it. This works well when rectangles
+/// have similar heights, like glyphs of one font.
+pub(crate) struct ShelfPacker {
+    width: u32,
+    height: u32,
+
+    /// The top of the current shelf, its height, and how far along it the
+    /// next rectangle goes.
+    shelf_y: u32,
+    shelf_height: u32,
+    cursor_x: u32,
+}
+
+impl ShelfPacker {
+    pub fn new(width: u32, height: u32) -> Self {
+        ShelfPacker {
+            width,
+            height,
+            shelf_y: 0,
+            shelf_height: 0,
+            cursor_x: 0,
+        }
+    }
+
+    /// Reserve a `width` x `height` rectangle, returning its top left
+    /// corner, or `None` if there is no room left.
+    pub fn pack(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width > self.width {
+            return None;
+        }
+
+        if self.cursor_x + width > self.width {
+            self.shelf_y += self.shelf_height;
+            self.shelf_height = 0;
+            self.cursor_x = 0;
+        }
+
+        if self.shelf_y + height > self.height {
+            return None;
+        }
+
+        let position = (self.cursor_x, self.shelf_y);
+        self.cursor_x += width;
+        self.shelf_height = self.shelf_height.max(height);
+
+        Some(position)
+    }
+}
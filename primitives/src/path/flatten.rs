@@ -0,0 +1,200 @@
+use super::PathCommand;
+use std::f32::consts::PI;
+
+/// The most line segments a single curve is split into.
+const MAX_SEGMENTS: usize = 1024;
+
+/// A run of connected points, started by a `MoveTo`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Subpath {
+    pub points: Vec<[f32; 2]>,
+
+    /// Set if the subpath ended with a `Close`, which doesn't add the first
+    /// point to `points` again.
+    pub closed: bool,
+}
+
+/// Approximate `commands` with line segments, each within `tolerance` (in
+/// data units) of the curve it replaces.
+pub(crate) fn flatten(commands: &[PathCommand], tolerance: f32) -> Vec<Subpath> {
+    let mut subpaths = Vec::new();
+    let mut current = Subpath {
+        points: vec![[0., 0.]],
+        closed: false,
+    };
+
+    for command in commands {
+        let from = *current.points.last().unwrap();
+
+        match *command {
+            PathCommand::MoveTo(to) => {
+                let previous = std::mem::replace(
+                    &mut current,
+                    Subpath {
+                        points: vec![to],
+                        closed: false,
+                    },
+                );
+                finish(previous, &mut subpaths);
+            }
+            PathCommand::LineTo(to) => current.points.push(to),
+            PathCommand::QuadTo { control, to } => {
+                let dd = length(add(sub(from, scale(control, 2.)), to));
+                let n = segments((dd / (4. * tolerance)).sqrt());
+
+                current.points.extend((1..=n).map(|i| {
+                    let t = i as f32 / n as f32;
+                    let mt = 1. - t;
+                    add(add(scale(from, mt * mt), scale(control, 2. * mt * t)), scale(to, t * t))
+                }));
+            }
+            PathCommand::CubicTo {
+                control1,
+                control2,
+                to,
+            } => {
+                let dd = length(add(sub(from, scale(control1, 2.)), control2))
+                    .max(length(add(sub(control1, scale(control2, 2.)), to)));
+                let n = segments((3. * dd / (4. * tolerance)).sqrt());
+
+                current.points.extend((1..=n).map(|i| {
+                    let t = i as f32 / n as f32;
+                    let mt = 1. - t;
+                    add(
+                        add(scale(from, mt * mt * mt), scale(control1, 3. * mt * mt * t)),
+                        add(scale(control2, 3. * mt * t * t), scale(to, t * t * t)),
+                    )
+                }));
+            }
+            PathCommand::ArcTo {
+                radii,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => arc(from, radii, rotation, large_arc, sweep, to, tolerance, &mut current.points),
+            PathCommand::Close => {
+                let start = current.points[0];
+                current.closed = true;
+                let previous = std::mem::replace(
+                    &mut current,
+                    Subpath {
+                        points: vec![start],
+                        closed: false,
+                    },
+                );
+                finish(previous, &mut subpaths);
+            }
+        }
+    }
+
+    finish(current, &mut subpaths);
+    subpaths
+}
+
+/// Keep a subpath if it has anything to draw.
+fn finish(subpath: Subpath, subpaths: &mut Vec<Subpath>) {
+    if subpath.points.len() > 1 {
+        subpaths.push(subpath);
+    }
+}
+
+fn segments(estimate: f32) -> usize {
+    if estimate.is_finite() {
+        (estimate.ceil() as usize).clamp(1, MAX_SEGMENTS)
+    } else {
+        1
+    }
+}
+
+/// Append points along an SVG elliptical arc, converting from its endpoint
+/// parameterization as described in the SVG spec's implementation notes.
+#[allow(clippy::too_many_arguments)]
+fn arc(
+    from: [f32; 2],
+    radii: [f32; 2],
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: [f32; 2],
+    tolerance: f32,
+    points: &mut Vec<[f32; 2]>,
+) {
+    if from == to {
+        return;
+    }
+
+    let [mut rx, mut ry] = radii.map(f32::abs);
+    if rx == 0. || ry == 0. {
+        points.push(to);
+        return;
+    }
+
+    let (sin, cos) = rotation.sin_cos();
+    let [dx, dy] = scale(sub(from, to), 0.5);
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    // Scale up radii too small to reach between the endpoints.
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let sign = if large_arc == sweep { -1. } else { 1. };
+    let coefficient = sign * (numerator / denominator).max(0.).sqrt();
+    let cx1 = coefficient * rx * y1 / ry;
+    let cy1 = -coefficient * ry * x1 / rx;
+
+    let [mx, my] = scale(add(from, to), 0.5);
+    let center = [cos * cx1 - sin * cy1 + mx, sin * cx1 + cos * cy1 + my];
+
+    let u = [(x1 - cx1) / rx, (y1 - cy1) / ry];
+    let v = [(-x1 - cx1) / rx, (-y1 - cy1) / ry];
+    let start_angle = angle([1., 0.], u);
+    let mut sweep_angle = angle(u, v) % (2. * PI);
+    if !sweep && sweep_angle > 0. {
+        sweep_angle -= 2. * PI;
+    } else if sweep && sweep_angle < 0. {
+        sweep_angle += 2. * PI;
+    }
+
+    // Each segment spans an angle whose chord stays within the tolerance of
+    // the larger radius.
+    let step = 2. * (1. - tolerance / rx.max(ry)).clamp(-1., 1.).acos();
+    let n = segments(sweep_angle.abs() / step);
+
+    points.extend((1..=n).map(|i| {
+        if i == n {
+            return to;
+        }
+
+        let theta = start_angle + sweep_angle * i as f32 / n as f32;
+        let (x, y) = (rx * theta.cos(), ry * theta.sin());
+        [center[0] + cos * x - sin * y, center[1] + sin * x + cos * y]
+    }));
+}
+
+fn add(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn scale(a: [f32; 2], s: f32) -> [f32; 2] {
+    [a[0] * s, a[1] * s]
+}
+
+fn length(a: [f32; 2]) -> f32 {
+    a[0].hypot(a[1])
+}
+
+/// The signed angle from `u` to `v`.
+fn angle(u: [f32; 2], v: [f32; 2]) -> f32 {
+    (u[0] * v[1] - u[1] * v[0]).atan2(u[0] * v[0] + u[1] * v[1])
+}
@@ -0,0 +1,275 @@
+mod flatten;
+
+pub use self::flatten::Subpath;
+
+use crate::{
+    color::Color,
+    common::{identity_line, layer_transform, layer_program, picking_program, picking_program_with_mode, LinePosition, Units},
+    polygon::{push_polygon, triangulate, PolygonVertex},
+    polyline::{push_segments, LineCap, LineJoin, Polyline, Segment},
+};
+use limelight::{
+    picking::Pickable,
+    renderer::Drawable,
+    shadow_gpu::UniformValue,
+    Buffer, BufferUsageHint, DrawMode, IndexBuffer, Program, Renderer, Result, Uniform,
+};
+use std::cell::{Cell, RefCell};
+
+/// How far flattened curves may stray from the true curve, in CSS pixels.
+const TOLERANCE: f32 = 0.25;
+
+/// One step of a path, like the commands of an SVG path's `d` attribute
+/// (in absolute coordinates).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PathCommand {
+    /// Start a new subpath.
+    MoveTo([f32; 2]),
+    LineTo([f32; 2]),
+    QuadTo {
+        control: [f32; 2],
+        to: [f32; 2],
+    },
+    CubicTo {
+        control1: [f32; 2],
+        control2: [f32; 2],
+        to: [f32; 2],
+    },
+    /// An elliptical arc, with the same parameters as SVG's `A` command
+    /// except that `rotation` is in radians.
+    ArcTo {
+        radii: [f32; 2],
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: [f32; 2],
+    },
+    /// Connect back to the start of the current subpath.
+    Close,
+}
+
+/// How to draw the outline of a path; see `Polyline`.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stroke {
+    pub width: f32,
+    pub units: Units,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub color: Color,
+}
+
+/// A shape made of lines and curves, which can be filled, stroked or both.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Path {
+    pub commands: Vec<PathCommand>,
+
+    /// The fill color. Subpaths after the first are treated as holes in it,
+    /// like the rings of a `Polygon`.
+    pub fill: Option<Color>,
+    pub stroke: Option<Stroke>,
+}
+
+impl Path {
+    /// Approximate the path with line segments, each within `tolerance` (in
+    /// data units) of the curve it replaces. This is what a `PathLayer`
+    /// draws, with a tolerance of a fraction of a pixel.
+    pub fn flatten(&self, tolerance: f32) -> Vec<Subpath> {
+        flatten::flatten(&self.commands, tolerance)
+    }
+}
+
+pub struct PathLayer {
+    paths: RefCell<Vec<Path>>,
+
+    /// The detail level the buffers were last built at, or `None` if the
+    /// paths changed since.
+    level: Cell<Option<i32>>,
+
+    segments: Buffer<Segment>,
+    line_positions: Buffer<LinePosition>,
+    stroke_program: Program<LinePosition, Segment>,
+    stroke_picking_program: Program<LinePosition, Segment>,
+
+    fill_vertices: Buffer<PolygonVertex>,
+    fill_indices: IndexBuffer,
+    fill_program: Program<PolygonVertex, ()>,
+    fill_picking_program: Program<PolygonVertex, ()>,
+
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+}
+
+impl Default for PathLayer {
+    fn default() -> Self {
+        PathLayer::new()
+    }
+}
+
+impl PathLayer {
+    /// Create a layer with its own identity transform.
+    pub fn new() -> Self {
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer with its own transform.
+    pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(Some(transform))
+    }
+
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let stroke_program = layer_program(
+            include_str!("../polyline/shader.vert"),
+            include_str!("../polyline/shader.frag"),
+            "path strokes",
+            DrawMode::TriangleStrip,
+            &transform,
+        );
+
+        let fill_program = layer_program(
+            include_str!("../polygon/shader.vert"),
+            include_str!("../polygon/shader.frag"),
+            "path fills",
+            DrawMode::Triangles,
+            &transform,
+        );
+
+        PathLayer {
+            paths: RefCell::new(Vec::new()),
+            level: Cell::new(None),
+            segments: Buffer::new_empty(BufferUsageHint::DynamicDraw),
+            line_positions: Buffer::new(identity_line(), BufferUsageHint::StaticDraw),
+            stroke_program,
+            stroke_picking_program: picking_program(
+                include_str!("../polyline/shader.vert"),
+                include_str!("../polyline/shader.frag"),
+                "path strokes",
+                &transform,
+            ),
+            fill_vertices: Buffer::new_empty(BufferUsageHint::DynamicDraw),
+            fill_indices: IndexBuffer::new_empty(BufferUsageHint::DynamicDraw),
+            fill_program,
+            fill_picking_program: picking_program_with_mode(
+                include_str!("../polygon/shader.vert"),
+                include_str!("../polygon/shader.frag"),
+                "path fills",
+                &transform,
+                DrawMode::Triangles,
+            ),
+            transform,
+        }
+    }
+
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
+        self.transform.clone()
+    }
+
+    /// Copy the paths currently in the layer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<Path> {
+        self.paths.borrow().clone()
+    }
+
+    /// Replace the paths in the layer. Picking reports the index of a path
+    /// in `data`.
+    pub fn import(&self, data: Vec<Path>) {
+        *self.paths.borrow_mut() = data;
+        self.level.set(None);
+    }
+
+    /// Flatten the paths again if they changed, or if the transform's scale
+    /// crossed a power of two since they were last flattened.
+    fn update(&self, renderer: &Renderer) {
+        let transform = layer_transform(&self.transform, renderer);
+        let resolution = match renderer.uniform_value("u_resolution") {
+            Some(UniformValue::Vec2(resolution)) => resolution,
+            _ => [1., 1.],
+        };
+
+        // CSS pixels per data unit, along the direction that is scaled most.
+        let [rx, ry] = resolution.map(|r| r / 2.);
+        let pixels_per_unit = (transform[0][0] * rx)
+            .hypot(transform[1][0] * ry)
+            .max((transform[0][1] * rx).hypot(transform[1][1] * ry));
+
+        let level = if pixels_per_unit > 0. && pixels_per_unit.is_finite() {
+            pixels_per_unit.log2().ceil() as i32
+        } else {
+            0
+        };
+        if self.level.get() == Some(level) {
+            return;
+        }
+
+        let tolerance = TOLERANCE / 2f32.powi(level);
+        let mut segments = Vec::new();
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for (index, path) in self.paths.borrow().iter().enumerate() {
+            let subpaths = path.flatten(tolerance);
+
+            if let Some(color) = path.fill {
+                let rings: Vec<Vec<[f32; 2]>> =
+                    subpaths.iter().map(|subpath| subpath.points.clone()).collect();
+                let triangles = triangulate(&rings);
+                push_polygon(&rings, &triangles, color, index as u32, &mut vertices, &mut indices);
+            }
+
+            if let Some(stroke) = &path.stroke {
+                for subpath in subpaths {
+                    let polyline = Polyline {
+                        points: subpath.points,
+                        closed: subpath.closed,
+                        width: stroke.width,
+                        units: stroke.units,
+                        join: stroke.join,
+                        cap: stroke.cap,
+                        color: stroke.color,
+                    };
+                    push_segments(&polyline, index as u32, &mut segments);
+                }
+            }
+        }
+
+        self.segments.set_data(segments);
+        self.fill_vertices.set_data(vertices);
+        self.fill_indices.set_data(indices);
+        self.level.set(Some(level));
+    }
+}
+
+impl Drawable for PathLayer {
+    /// Fills are drawn beneath all strokes.
+    fn draw(&mut self, renderer: &mut Renderer) -> Result<()> {
+        self.update(renderer);
+        renderer.render_indexed(&mut self.fill_program, &self.fill_vertices, &self.fill_indices)?;
+        renderer.render_instanced(&mut self.stroke_program, &self.line_positions, &self.segments)?;
+
+        Ok(())
+    }
+}
+
+impl Pickable for PathLayer {
+    fn draw_picking(&mut self, renderer: &mut Renderer) -> Result<()> {
+        self.update(renderer);
+        renderer.render_indexed(
+            &mut self.fill_picking_program,
+            &self.fill_vertices,
+            &self.fill_indices,
+        )?;
+        renderer.render_instanced(
+            &mut self.stroke_picking_program,
+            &self.line_positions,
+            &self.segments,
+        )?;
+
+        Ok(())
+    }
+}
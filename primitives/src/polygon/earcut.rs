@@ -0,0 +1,570 @@
+//! Ear clipping triangulation of polygons with holes, following Mapbox's
+//! `earcut` (without its z-order hashing): holes are bridged into the outer
+//! ring, then ears are clipped from the resulting single ring, with fallbacks
+//! for self-intersecting and degenerate input.
+
+struct Node {
+    /// Index of the point in the flattened rings.
+    i: u32,
+    x: f64,
+    y: f64,
+    prev: usize,
+    next: usize,
+
+    /// Set on single-point holes, which must not be filtered out.
+    steiner: bool,
+}
+
+struct Earcut {
+    nodes: Vec<Node>,
+    triangles: Vec<u32>,
+}
+
+/// Triangulate a polygon given as an outer ring followed by holes, in either
+/// winding order. Returns indices into the rings' points, concatenated.
+pub(crate) fn triangulate(rings: &[Vec<[f32; 2]>]) -> Vec<u32> {
+    let mut earcut = Earcut {
+        nodes: Vec::new(),
+        triangles: Vec::new(),
+    };
+
+    let mut offset = 0;
+    let mut outer = None;
+    let mut holes = Vec::new();
+    for (index, ring) in rings.iter().enumerate() {
+        let list = earcut.linked_list(ring, offset, index == 0);
+        offset += ring.len() as u32;
+
+        if index == 0 {
+            outer = list;
+        } else if let Some(list) = list {
+            holes.push(list);
+        }
+    }
+
+    let mut outer = match outer {
+        Some(outer) if earcut.next(outer) != earcut.prev(outer) => outer,
+        _ => return Vec::new(),
+    };
+
+    if !holes.is_empty() {
+        outer = earcut.eliminate_holes(holes, outer);
+    }
+
+    earcut.earcut_linked(outer, 0);
+    earcut.triangles
+}
+
+impl Earcut {
+    fn prev(&self, p: usize) -> usize {
+        self.nodes[p].prev
+    }
+
+    fn next(&self, p: usize) -> usize {
+        self.nodes[p].next
+    }
+
+    /// Twice the signed area of the triangle `p`, `q`, `r`; negative when
+    /// the turn at `q` is convex for the winding used internally.
+    fn area(&self, p: usize, q: usize, r: usize) -> f64 {
+        let (p, q, r) = (&self.nodes[p], &self.nodes[q], &self.nodes[r]);
+        (q.y - p.y) * (r.x - q.x) - (q.x - p.x) * (r.y - q.y)
+    }
+
+    fn equals(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (&self.nodes[a], &self.nodes[b]);
+        a.x == b.x && a.y == b.y
+    }
+
+    fn insert_node(&mut self, i: u32, point: [f32; 2], last: Option<usize>) -> usize {
+        let p = self.nodes.len();
+        let (prev, next) = match last {
+            Some(last) => (last, self.next(last)),
+            None => (p, p),
+        };
+
+        self.nodes.push(Node {
+            i,
+            x: point[0] as f64,
+            y: point[1] as f64,
+            prev,
+            next,
+            steiner: false,
+        });
+
+        if let Some(last) = last {
+            let next = self.next(last);
+            self.nodes[next].prev = p;
+            self.nodes[last].next = p;
+        }
+
+        p
+    }
+
+    /// Unlink `p`, leaving its own links in place so that callers can still
+    /// step from it to its former neighbors.
+    fn remove_node(&mut self, p: usize) {
+        let (prev, next) = (self.prev(p), self.next(p));
+        self.nodes[next].prev = prev;
+        self.nodes[prev].next = next;
+    }
+
+    /// Create a circular list from a ring, wound clockwise for the outer ring
+    /// and counterclockwise for holes. Returns its last node.
+    fn linked_list(&mut self, ring: &[[f32; 2]], offset: u32, clockwise: bool) -> Option<usize> {
+        let mut area = 0.;
+        for (i, point) in ring.iter().enumerate() {
+            let prev = ring[(i + ring.len() - 1) % ring.len()];
+            area += (prev[0] as f64 - point[0] as f64) * (point[1] as f64 + prev[1] as f64);
+        }
+
+        let mut last = None;
+        if clockwise == (area > 0.) {
+            for (i, point) in ring.iter().enumerate() {
+                last = Some(self.insert_node(offset + i as u32, *point, last));
+            }
+        } else {
+            for (i, point) in ring.iter().enumerate().rev() {
+                last = Some(self.insert_node(offset + i as u32, *point, last));
+            }
+        }
+
+        if let Some(p) = last {
+            if self.equals(p, self.next(p)) {
+                self.remove_node(p);
+                last = Some(self.next(p));
+            }
+        }
+
+        last
+    }
+
+    /// Remove duplicate and collinear points between `start` and `end`.
+    fn filter_points(&mut self, start: usize, end: Option<usize>) -> usize {
+        let mut end = end.unwrap_or(start);
+        let mut p = start;
+
+        loop {
+            let mut again = false;
+
+            if !self.nodes[p].steiner
+                && (self.equals(p, self.next(p)) || self.area(self.prev(p), p, self.next(p)) == 0.)
+            {
+                self.remove_node(p);
+                p = self.prev(p);
+                end = p;
+                if p == self.next(p) {
+                    break;
+                }
+                again = true;
+            } else {
+                p = self.next(p);
+            }
+
+            if !again && p == end {
+                break;
+            }
+        }
+
+        end
+    }
+
+    /// Clip ears until one triangle is left. If no ear can be found, retry
+    /// after filtering points, then after curing self-intersections, and
+    /// finally by splitting the polygon in two.
+    fn earcut_linked(&mut self, mut ear: usize, pass: u8) {
+        let mut stop = ear;
+
+        while self.prev(ear) != self.next(ear) {
+            let (prev, next) = (self.prev(ear), self.next(ear));
+
+            if self.is_ear(ear) {
+                self.triangles
+                    .extend([self.nodes[prev].i, self.nodes[ear].i, self.nodes[next].i]);
+                self.remove_node(ear);
+
+                // Skipping the next vertex leads to fewer sliver triangles.
+                ear = self.next(next);
+                stop = ear;
+                continue;
+            }
+
+            ear = next;
+
+            if ear == stop {
+                match pass {
+                    0 => {
+                        let ear = self.filter_points(ear, None);
+                        self.earcut_linked(ear, 1);
+                    }
+                    1 => {
+                        let ear = self.filter_points(ear, None);
+                        let ear = self.cure_local_intersections(ear);
+                        self.earcut_linked(ear, 2);
+                    }
+                    _ => self.split_earcut(ear),
+                }
+                break;
+            }
+        }
+    }
+
+    /// Whether `ear` is convex with no other point inside its triangle.
+    fn is_ear(&self, ear: usize) -> bool {
+        let (a, b, c) = (self.prev(ear), ear, self.next(ear));
+        if self.area(a, b, c) >= 0. {
+            return false;
+        }
+
+        let (na, nb, nc) = (&self.nodes[a], &self.nodes[b], &self.nodes[c]);
+        let mut p = self.next(c);
+        while p != a {
+            let np = &self.nodes[p];
+            if point_in_triangle([na.x, na.y], [nb.x, nb.y], [nc.x, nc.y], [np.x, np.y])
+                && self.area(self.prev(p), p, self.next(p)) >= 0.
+            {
+                return false;
+            }
+            p = self.next(p);
+        }
+
+        true
+    }
+
+    /// Clip triangles at places where two adjacent edges cross.
+    fn cure_local_intersections(&mut self, mut start: usize) -> usize {
+        let mut p = start;
+
+        loop {
+            let a = self.prev(p);
+            let b = self.next(self.next(p));
+
+            if !self.equals(a, b)
+                && self.intersects(a, p, self.next(p), b)
+                && self.locally_inside(a, b)
+                && self.locally_inside(b, a)
+            {
+                self.triangles
+                    .extend([self.nodes[a].i, self.nodes[p].i, self.nodes[b].i]);
+
+                let next = self.next(p);
+                self.remove_node(p);
+                self.remove_node(next);
+
+                p = b;
+                start = b;
+            }
+
+            p = self.next(p);
+            if p == start {
+                break;
+            }
+        }
+
+        self.filter_points(p, None)
+    }
+
+    /// Split the polygon along a valid diagonal and triangulate both halves.
+    fn split_earcut(&mut self, start: usize) {
+        let mut a = start;
+
+        loop {
+            let mut b = self.next(self.next(a));
+            while b != self.prev(a) {
+                if self.nodes[a].i != self.nodes[b].i && self.is_valid_diagonal(a, b) {
+                    let c = self.split_polygon(a, b);
+
+                    let a = self.filter_points(a, Some(self.next(a)));
+                    let c = self.filter_points(c, Some(self.next(c)));
+
+                    self.earcut_linked(a, 0);
+                    self.earcut_linked(c, 0);
+                    return;
+                }
+                b = self.next(b);
+            }
+
+            a = self.next(a);
+            if a == start {
+                break;
+            }
+        }
+    }
+
+    /// Connect each hole to the outer ring, from left to right.
+    fn eliminate_holes(&mut self, holes: Vec<usize>, mut outer: usize) -> usize {
+        let mut queue: Vec<usize> = holes
+            .into_iter()
+            .map(|list| {
+                if self.next(list) == list {
+                    self.nodes[list].steiner = true;
+                }
+                self.leftmost(list)
+            })
+            .collect();
+        queue.sort_by(|&a, &b| {
+            let (a, b) = (&self.nodes[a], &self.nodes[b]);
+            a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+        });
+
+        for hole in queue {
+            outer = self.eliminate_hole(hole, outer);
+        }
+
+        outer
+    }
+
+    fn eliminate_hole(&mut self, hole: usize, outer: usize) -> usize {
+        let bridge = match self.find_hole_bridge(hole, outer) {
+            Some(bridge) => bridge,
+            None => return outer,
+        };
+
+        let bridge_reverse = self.split_polygon(bridge, hole);
+        self.filter_points(bridge_reverse, Some(self.next(bridge_reverse)));
+        self.filter_points(bridge, Some(self.next(bridge)))
+    }
+
+    /// Find a point on the outer ring that can be connected to the leftmost
+    /// point of a hole without crossing any edges.
+    fn find_hole_bridge(&self, hole: usize, outer: usize) -> Option<usize> {
+        let (hx, hy) = (self.nodes[hole].x, self.nodes[hole].y);
+        let mut qx = f64::NEG_INFINITY;
+        let mut m = None;
+
+        // Find the segment to the left of the hole point that a ray cast
+        // leftwards from it hits first.
+        let mut p = outer;
+        loop {
+            let (np, nn) = (&self.nodes[p], &self.nodes[self.next(p)]);
+            if hy <= np.y && hy >= nn.y && nn.y != np.y {
+                let x = np.x + (hy - np.y) * (nn.x - np.x) / (nn.y - np.y);
+                if x <= hx && x > qx {
+                    qx = x;
+                    m = Some(if np.x < nn.x { p } else { self.next(p) });
+                    if x == hx {
+                        // The hole touches the outer segment.
+                        return m;
+                    }
+                }
+            }
+
+            p = self.next(p);
+            if p == outer {
+                break;
+            }
+        }
+
+        let mut m = m?;
+
+        // Points inside the triangle formed by the hole point, the ray's
+        // intersection and the segment's endpoint could block the bridge;
+        // if there are any, use the one with the smallest angle to the ray.
+        let stop = m;
+        let (mx, my) = (self.nodes[m].x, self.nodes[m].y);
+        let mut tan_min = f64::INFINITY;
+
+        p = m;
+        loop {
+            let (px, py) = (self.nodes[p].x, self.nodes[p].y);
+            if hx >= px
+                && px >= mx
+                && hx != px
+                && point_in_triangle(
+                    [if hy < my { hx } else { qx }, hy],
+                    [mx, my],
+                    [if hy < my { qx } else { hx }, hy],
+                    [px, py],
+                )
+            {
+                let tan = (hy - py).abs() / (hx - px);
+                let nm = &self.nodes[m];
+
+                if self.locally_inside(p, hole)
+                    && (tan < tan_min
+                        || (tan == tan_min
+                            && (px > nm.x || (px == nm.x && self.sector_contains_sector(m, p)))))
+                {
+                    m = p;
+                    tan_min = tan;
+                }
+            }
+
+            p = self.next(p);
+            if p == stop {
+                break;
+            }
+        }
+
+        Some(m)
+    }
+
+    fn sector_contains_sector(&self, m: usize, p: usize) -> bool {
+        self.area(self.prev(m), m, self.prev(p)) < 0. && self.area(self.next(p), m, self.next(m)) < 0.
+    }
+
+    fn leftmost(&self, start: usize) -> usize {
+        let mut p = start;
+        let mut leftmost = start;
+
+        loop {
+            let (np, nl) = (&self.nodes[p], &self.nodes[leftmost]);
+            if np.x < nl.x || (np.x == nl.x && np.y < nl.y) {
+                leftmost = p;
+            }
+
+            p = self.next(p);
+            if p == start {
+                break;
+            }
+        }
+
+        leftmost
+    }
+
+    /// Whether a diagonal between `a` and `b` lies inside the polygon
+    /// without crossing any edges.
+    fn is_valid_diagonal(&self, a: usize, b: usize) -> bool {
+        let ib = self.nodes[b].i;
+
+        self.nodes[self.next(a)].i != ib
+            && self.nodes[self.prev(a)].i != ib
+            && !self.intersects_polygon(a, b)
+            && ((self.locally_inside(a, b)
+                && self.locally_inside(b, a)
+                && self.middle_inside(a, b)
+                // Doesn't create opposite-facing sectors.
+                && (self.area(self.prev(a), a, self.prev(b)) != 0.
+                    || self.area(a, self.prev(b), b) != 0.))
+                // A zero-length diagonal between coincident points.
+                || (self.equals(a, b)
+                    && self.area(self.prev(a), a, self.next(a)) > 0.
+                    && self.area(self.prev(b), b, self.next(b)) > 0.))
+    }
+
+    fn intersects(&self, p1: usize, q1: usize, p2: usize, q2: usize) -> bool {
+        let o1 = sign(self.area(p1, q1, p2));
+        let o2 = sign(self.area(p1, q1, q2));
+        let o3 = sign(self.area(p2, q2, p1));
+        let o4 = sign(self.area(p2, q2, q1));
+
+        (o1 != o2 && o3 != o4)
+            || (o1 == 0 && self.on_segment(p1, p2, q1))
+            || (o2 == 0 && self.on_segment(p1, q2, q1))
+            || (o3 == 0 && self.on_segment(p2, p1, q2))
+            || (o4 == 0 && self.on_segment(p2, q1, q2))
+    }
+
+    /// Whether `q` lies within the bounding box of `p` and `r`, given that
+    /// the three are collinear.
+    fn on_segment(&self, p: usize, q: usize, r: usize) -> bool {
+        let (p, q, r) = (&self.nodes[p], &self.nodes[q], &self.nodes[r]);
+        q.x <= p.x.max(r.x) && q.x >= p.x.min(r.x) && q.y <= p.y.max(r.y) && q.y >= p.y.min(r.y)
+    }
+
+    fn intersects_polygon(&self, a: usize, b: usize) -> bool {
+        let (ia, ib) = (self.nodes[a].i, self.nodes[b].i);
+        let mut p = a;
+
+        loop {
+            let next = self.next(p);
+            let (ip, inext) = (self.nodes[p].i, self.nodes[next].i);
+            if ip != ia && inext != ia && ip != ib && inext != ib && self.intersects(p, next, a, b) {
+                return true;
+            }
+
+            p = next;
+            if p == a {
+                return false;
+            }
+        }
+    }
+
+    /// Whether the diagonal from `a` to `b` starts inside the polygon at `a`.
+    fn locally_inside(&self, a: usize, b: usize) -> bool {
+        let (prev, next) = (self.prev(a), self.next(a));
+        if self.area(prev, a, next) < 0. {
+            self.area(a, b, next) >= 0. && self.area(a, prev, b) >= 0.
+        } else {
+            self.area(a, b, prev) < 0. || self.area(a, next, b) < 0.
+        }
+    }
+
+    /// Whether the midpoint of the diagonal from `a` to `b` is inside the
+    /// polygon.
+    fn middle_inside(&self, a: usize, b: usize) -> bool {
+        let (px, py) = (
+            (self.nodes[a].x + self.nodes[b].x) / 2.,
+            (self.nodes[a].y + self.nodes[b].y) / 2.,
+        );
+        let mut inside = false;
+        let mut p = a;
+
+        loop {
+            let (np, nn) = (&self.nodes[p], &self.nodes[self.next(p)]);
+            if (np.y > py) != (nn.y > py)
+                && nn.y != np.y
+                && px < (nn.x - np.x) * (py - np.y) / (nn.y - np.y) + np.x
+            {
+                inside = !inside;
+            }
+
+            p = self.next(p);
+            if p == a {
+                return inside;
+            }
+        }
+    }
+
+    /// Link `a` and `b` with a diagonal, splitting the ring in two. If they
+    /// are on different rings, this instead merges them into one. Returns
+    /// the copy of `b` on the second ring.
+    fn split_polygon(&mut self, a: usize, b: usize) -> usize {
+        let a2 = self.nodes.len();
+        let b2 = a2 + 1;
+        let (an, bp) = (self.next(a), self.prev(b));
+
+        for original in [a, b] {
+            let node = &self.nodes[original];
+            let copy = Node {
+                i: node.i,
+                x: node.x,
+                y: node.y,
+                prev: 0,
+                next: 0,
+                steiner: false,
+            };
+            self.nodes.push(copy);
+        }
+
+        self.nodes[a].next = b;
+        self.nodes[b].prev = a;
+
+        self.nodes[a2].next = an;
+        self.nodes[an].prev = a2;
+
+        self.nodes[b2].next = a2;
+        self.nodes[a2].prev = b2;
+
+        self.nodes[bp].next = b2;
+        self.nodes[b2].prev = bp;
+
+        b2
+    }
+}
+
+fn sign(value: f64) -> i8 {
+    if value > 0. {
+        1
+    } else if value < 0. {
+        -1
+    } else {
+        0
+    }
+}
+
+fn point_in_triangle(a: [f64; 2], b: [f64; 2], c: [f64; 2], p: [f64; 2]) -> bool {
+    (c[0] - p[0]) * (a[1] - p[1]) >= (a[0] - p[0]) * (c[1] - p[1])
+        && (a[0] - p[0]) * (b[1] - p[1]) >= (b[0] - p[0]) * (a[1] - p[1])
+        && (b[0] - p[0]) * (c[1] - p[1]) >= (c[0] - p[0]) * (b[1] - p[1])
+}
@@ -0,0 +1,205 @@
+mod earcut;
+
+pub(crate) use self::earcut::triangulate;
+
+use crate::{color::Color, common::{layer_program, picking_program_with_mode}};
+use limelight::{
+    attribute,
+    picking::Pickable,
+    renderer::Drawable,
+    Buffer, BufferUsageHint, DrawMode, IndexBuffer, Program, Result, Uniform,
+};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+/// A filled polygon, possibly with holes.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polygon {
+    /// The outer ring followed by any holes, as in GeoJSON. Rings may be
+    /// wound either way and don't need to repeat their first point.
+    pub rings: Vec<Vec<[f32; 2]>>,
+    pub color: Color,
+}
+
+#[attribute]
+pub(crate) struct PolygonVertex {
+    position: [f32; 2],
+    color: Color,
+
+    /// The index of the polygon the vertex belongs to, for picking.
+    polygon: u32,
+}
+
+/// A polygon's rings, compared bit for bit so that they can key the cache of
+/// triangulations.
+#[derive(PartialEq, Eq, Hash)]
+struct RingsKey(Vec<Vec<[u32; 2]>>);
+
+impl RingsKey {
+    fn new(rings: &[Vec<[f32; 2]>]) -> Self {
+        RingsKey(
+            rings
+                .iter()
+                .map(|ring| ring.iter().map(|point| point.map(f32::to_bits)).collect())
+                .collect(),
+        )
+    }
+}
+
+pub struct PolygonLayer {
+    polygons: RefCell<Vec<Polygon>>,
+
+    /// The triangulation of each polygon, by index.
+    triangulations: RefCell<Vec<Rc<Vec<u32>>>>,
+
+    /// The triangulations of the current polygons, by their rings, so that
+    /// they can be reused when a polygon moves to another index.
+    cache: RefCell<HashMap<RingsKey, Rc<Vec<u32>>>>,
+    vertices: Buffer<PolygonVertex>,
+    indices: IndexBuffer,
+    program: Program<PolygonVertex, ()>,
+    picking_program: Program<PolygonVertex, ()>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+}
+
+impl Default for PolygonLayer {
+    fn default() -> Self {
+        PolygonLayer::new()
+    }
+}
+
+impl PolygonLayer {
+    /// Create a layer with its own identity transform.
+    pub fn new() -> Self {
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer with its own transform.
+    pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(Some(transform))
+    }
+
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let program = layer_program(
+            include_str!("shader.vert"),
+            include_str!("shader.frag"),
+            "polygons",
+            DrawMode::Triangles,
+            &transform,
+        );
+
+        PolygonLayer {
+            polygons: RefCell::new(Vec::new()),
+            triangulations: RefCell::new(Vec::new()),
+            cache: RefCell::new(HashMap::new()),
+            vertices: Buffer::new_empty(BufferUsageHint::DynamicDraw),
+            indices: IndexBuffer::new_empty(BufferUsageHint::DynamicDraw),
+            program,
+            picking_program: picking_program_with_mode(
+                include_str!("shader.vert"),
+                include_str!("shader.frag"),
+                "polygons",
+                &transform,
+                DrawMode::Triangles,
+            ),
+            transform,
+        }
+    }
+
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
+        self.transform.clone()
+    }
+
+    /// Copy the polygons currently in the layer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<Polygon> {
+        self.polygons.borrow().clone()
+    }
+
+    /// Replace the polygons in the layer. Polygons with the same rings as
+    /// one already in the layer, such as ones that only changed color or
+    /// moved to another index, aren't triangulated again. Picking reports
+    /// the index of a polygon in `data`.
+    pub fn import(&self, data: Vec<Polygon>) {
+        let mut cache = self.cache.borrow_mut();
+        let mut next_cache = HashMap::with_capacity(data.len());
+        let mut triangulations = Vec::with_capacity(data.len());
+
+        for polygon in &data {
+            let key = RingsKey::new(&polygon.rings);
+            let triangles = match cache.remove(&key).or_else(|| next_cache.get(&key).cloned()) {
+                Some(triangles) => triangles,
+                None => Rc::new(triangulate(&polygon.rings)),
+            };
+
+            next_cache.insert(key, triangles.clone());
+            triangulations.push(triangles);
+        }
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for (index, (polygon, triangles)) in data.iter().zip(&triangulations).enumerate() {
+            push_polygon(
+                &polygon.rings,
+                triangles,
+                polygon.color,
+                index as u32,
+                &mut vertices,
+                &mut indices,
+            );
+        }
+
+        self.vertices.set_data(vertices);
+        self.indices.set_data(indices);
+        *self.polygons.borrow_mut() = data;
+        *self.triangulations.borrow_mut() = triangulations;
+        *cache = next_cache;
+    }
+
+    /// The triangles of the polygon at `index`, as indices into its rings'
+    /// points concatenated, or `None` if there is no such polygon.
+    pub fn triangles(&self, index: usize) -> Option<Rc<Vec<u32>>> {
+        self.triangulations.borrow().get(index).cloned()
+    }
+}
+
+/// Append the vertices of `rings` and the indices of their `triangles`,
+/// offset past the vertices already in `vertices`.
+pub(crate) fn push_polygon(
+    rings: &[Vec<[f32; 2]>],
+    triangles: &[u32],
+    color: Color,
+    index: u32,
+    vertices: &mut Vec<PolygonVertex>,
+    indices: &mut Vec<u32>,
+) {
+    let offset = vertices.len() as u32;
+    indices.extend(triangles.iter().map(|i| i + offset));
+
+    vertices.extend(rings.iter().flatten().map(|&position| PolygonVertex {
+        position,
+        color,
+        polygon: index,
+    }));
+}
+
+impl Drawable for PolygonLayer {
+    fn draw(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_indexed(&mut self.program, &self.vertices, &self.indices)?;
+
+        Ok(())
+    }
+}
+
+impl Pickable for PolygonLayer {
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_indexed(&mut self.picking_program, &self.vertices, &self.indices)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+#version 300 es
+
+in vec2 position;
+in uint color;
+in uint polygon;
+
+flat out uint v_color;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
+uniform mat4 u_transform;
+
+void main() {
+    gl_Position = vec4(position, 0., 1.) * u_transform;
+
+    v_color = color;
+
+#ifdef PICKING
+    v_instance = polygon;
+#endif
+}
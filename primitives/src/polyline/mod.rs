@@ -0,0 +1,341 @@
+use crate::{color::Color, common::{layer_program, picking_program, LinePosition, Units, identity_line}};
+use limelight::{
+    attribute,
+    picking::Pickable,
+    renderer::Drawable,
+    Buffer, BufferUsageHint, DrawMode, Program, Result, Uniform,
+};
+use std::cell::RefCell;
+
+/// How two segments of a polyline are connected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineJoin {
+    /// Extend the outer edges until they meet, falling back to `Bevel` for
+    /// joins more than four times as long as the line is wide.
+    #[default]
+    Miter,
+    Bevel,
+    Round,
+}
+
+/// How the ends of an open polyline are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineCap {
+    /// End exactly at the first and last points.
+    #[default]
+    Butt,
+    /// Extend past the first and last points by half the width.
+    Square,
+    Round,
+}
+
+/// A connected series of line segments.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polyline {
+    pub points: Vec<[f32; 2]>,
+
+    /// Whether to connect the last point back to the first.
+    pub closed: bool,
+
+    /// The total width of the line (unlike `Line`, which extends `width` to
+    /// either side).
+    pub width: f32,
+    pub units: Units,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub color: Color,
+}
+
+/// How one end of a `Segment` is drawn, matching the constants in the
+/// shaders.
+const CAP_BUTT: u32 = 0;
+const CAP_SQUARE: u32 = 1;
+const CAP_ROUND: u32 = 2;
+const JOIN_MITER: u32 = 3;
+const JOIN_BEVEL: u32 = 4;
+const JOIN_ROUND: u32 = 5;
+
+/// Set in `Segment::style` when the width is in screen units.
+const SCREEN_UNITS: u32 = 1 << 8;
+
+/// One segment of a polyline, along with the points before and after it so
+/// that the shader can draw its share of the joins.
+#[attribute]
+pub(crate) struct Segment {
+    prev: [f32; 2],
+    start: [f32; 2],
+    end: [f32; 2],
+    next: [f32; 2],
+    width: f32,
+    color: Color,
+
+    /// How the start (bits 0-3) and end (bits 4-7) are drawn, and whether
+    /// the width is in screen units.
+    style: u32,
+
+    /// The index of the polyline the segment belongs to, for picking.
+    path: u32,
+}
+
+/// Append the segments of `polyline` to `segments`. Consecutive duplicate
+/// points are skipped, since they have no direction to join along.
+pub(crate) fn push_segments(polyline: &Polyline, index: u32, segments: &mut Vec<Segment>) {
+    let mut points = polyline.points.clone();
+    points.dedup();
+    if polyline.closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+
+    let n = points.len();
+    let closed = polyline.closed && n > 2;
+    if n < 2 {
+        return;
+    }
+
+    let cap = match polyline.cap {
+        LineCap::Butt => CAP_BUTT,
+        LineCap::Square => CAP_SQUARE,
+        LineCap::Round => CAP_ROUND,
+    };
+    let join = match polyline.join {
+        LineJoin::Miter => JOIN_MITER,
+        LineJoin::Bevel => JOIN_BEVEL,
+        LineJoin::Round => JOIN_ROUND,
+    };
+    let units = match polyline.units {
+        Units::World => 0,
+        Units::Screen => SCREEN_UNITS,
+    };
+
+    let count = if closed { n } else { n - 1 };
+    for i in 0..count {
+        let (start, end) = (points[i], points[(i + 1) % n]);
+        let has_prev = closed || i > 0;
+        let has_next = closed || i + 2 < n;
+
+        let prev = if has_prev { points[(i + n - 1) % n] } else { start };
+        let next = if has_next { points[(i + 2) % n] } else { end };
+        let start_style = if has_prev { join } else { cap };
+        let end_style = if has_next { join } else { cap };
+
+        segments.push(Segment {
+            prev,
+            start,
+            end,
+            next,
+            width: polyline.width,
+            color: polyline.color,
+            style: start_style | end_style << 4 | units,
+            path: index,
+        });
+    }
+}
+
+pub struct PolylineLayer {
+    polylines: RefCell<Vec<Polyline>>,
+    segments: Buffer<Segment>,
+    positions: Buffer<LinePosition>,
+    program: Program<LinePosition, Segment>,
+    picking_program: Program<LinePosition, Segment>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+}
+
+impl Default for PolylineLayer {
+    fn default() -> Self {
+        PolylineLayer::new()
+    }
+}
+
+impl PolylineLayer {
+    /// Create a layer with its own identity transform.
+    pub fn new() -> Self {
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer with its own transform.
+    pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(Some(transform))
+    }
+
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let program = layer_program(
+            include_str!("shader.vert"),
+            include_str!("shader.frag"),
+            "polylines",
+            DrawMode::TriangleStrip,
+            &transform,
+        );
+
+        PolylineLayer {
+            polylines: RefCell::new(Vec::new()),
+            segments: Buffer::new_empty(BufferUsageHint::DynamicDraw),
+            positions: Buffer::new(identity_line(), BufferUsageHint::StaticDraw),
+            program,
+            picking_program: picking_program(
+                include_str!("shader.vert"),
+                include_str!("shader.frag"),
+                "polylines",
+                &transform,
+            ),
+            transform,
+        }
+    }
+
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
+        self.transform.clone()
+    }
+
+    /// Copy the polylines currently in the layer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<Polyline> {
+        self.polylines.borrow().clone()
+    }
+
+    /// Replace the polylines in the layer. Picking reports the index of a
+    /// polyline in `data`.
+    pub fn import(&self, data: Vec<Polyline>) {
+        let mut segments = Vec::new();
+        for (index, polyline) in data.iter().enumerate() {
+            push_segments(polyline, index as u32, &mut segments);
+        }
+
+        self.segments.set_data(segments);
+        *self.polylines.borrow_mut() = data;
+    }
+}
+
+impl Drawable for PolylineLayer {
+    fn draw(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.program, &self.positions, &self.segments)?;
+
+        Ok(())
+    }
+}
+
+impl Pickable for PolylineLayer {
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.picking_program, &self.positions, &self.segments)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polyline(points: Vec<[f32; 2]>, closed: bool) -> Polyline {
+        Polyline {
+            points,
+            closed,
+            width: 1.,
+            units: Units::World,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            color: Color::default(),
+        }
+    }
+
+    fn segments(polyline: &Polyline) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        push_segments(polyline, 7, &mut segments);
+        segments
+    }
+
+    /// Each segment's `(prev, start, end, next)`.
+    fn ends(segments: &[Segment]) -> Vec<[[f32; 2]; 4]> {
+        segments
+            .iter()
+            .map(|segment| [segment.prev, segment.start, segment.end, segment.next])
+            .collect()
+    }
+
+    #[test]
+    fn test_consecutive_duplicates_are_dropped() {
+        // A single distinct point has nothing to draw.
+        assert!(segments(&polyline(vec![[1., 1.], [1., 1.]], false)).is_empty());
+
+        let segments = segments(&polyline(
+            vec![[0., 0.], [0., 0.], [1., 0.], [1., 0.], [1., 0.], [1., 1.]],
+            false,
+        ));
+
+        assert_eq!(
+            vec![
+                [[0., 0.], [0., 0.], [1., 0.], [1., 1.]],
+                [[0., 0.], [1., 0.], [1., 1.], [1., 1.]],
+            ],
+            ends(&segments)
+        );
+        assert!(segments.iter().all(|segment| segment.path == 7));
+    }
+
+    #[test]
+    fn test_closed_ring_wraps_around() {
+        let square = vec![[0., 0.], [1., 0.], [1., 1.], [0., 1.]];
+        let expected = vec![
+            [[0., 1.], [0., 0.], [1., 0.], [1., 1.]],
+            [[0., 0.], [1., 0.], [1., 1.], [0., 1.]],
+            [[1., 0.], [1., 1.], [0., 1.], [0., 0.]],
+            [[1., 1.], [0., 1.], [0., 0.], [1., 0.]],
+        ];
+        assert_eq!(expected, ends(&segments(&polyline(square.clone(), true))));
+
+        // Repeating the first point at the end doesn't add a zero-length
+        // segment.
+        let mut repeated = square;
+        repeated.push([0., 0.]);
+        assert_eq!(expected, ends(&segments(&polyline(repeated, true))));
+
+        // Every end of a closed ring is a join.
+        let ring = segments(&polyline(vec![[0., 0.], [1., 0.], [1., 1.]], true));
+        assert!(ring
+            .iter()
+            .all(|segment| segment.style == JOIN_MITER | JOIN_MITER << 4));
+    }
+
+    #[test]
+    fn test_closed_with_two_points_is_drawn_open() {
+        for points in [vec![[0., 0.], [1., 0.]], vec![[0., 0.], [1., 0.], [0., 0.]]] {
+            let segments = segments(&polyline(points, true));
+
+            assert_eq!(vec![[[0., 0.], [0., 0.], [1., 0.], [1., 0.]]], ends(&segments));
+            assert_eq!(CAP_BUTT | CAP_BUTT << 4, segments[0].style);
+        }
+    }
+
+    #[test]
+    fn test_style_packs_caps_joins_and_units() {
+        let mut line = polyline(vec![[0., 0.], [1., 0.], [1., 1.]], false);
+        line.cap = LineCap::Round;
+        line.join = LineJoin::Bevel;
+        line.units = Units::Screen;
+
+        let styles: Vec<u32> = segments(&line).iter().map(|segment| segment.style).collect();
+        assert_eq!(
+            vec![
+                CAP_ROUND | JOIN_BEVEL << 4 | SCREEN_UNITS,
+                JOIN_BEVEL | CAP_ROUND << 4 | SCREEN_UNITS,
+            ],
+            styles
+        );
+
+        line.cap = LineCap::Square;
+        line.join = LineJoin::Round;
+        line.units = Units::World;
+
+        let styles: Vec<u32> = segments(&line).iter().map(|segment| segment.style).collect();
+        assert_eq!(
+            vec![CAP_SQUARE | JOIN_ROUND << 4, JOIN_ROUND | CAP_SQUARE << 4],
+            styles
+        );
+    }
+}
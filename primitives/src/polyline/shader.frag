@@ -0,0 +1,98 @@
+#version 300 es
+
+precision highp float;
+
+flat in uint v_color;
+flat in uint v_style;
+flat in float v_length;
+flat in float v_half_width;
+flat in vec4 v_neighbors;
+in vec2 v_local;
+
+#ifdef PICKING
+flat in uint v_instance;
+uniform uint u_pick_id;
+out uvec4 f_id;
+#else
+out vec4 f_color;
+#endif
+
+const uint CAP_SQUARE = 1u;
+const uint CAP_ROUND = 2u;
+const uint JOIN_MITER = 3u;
+const uint JOIN_BEVEL = 4u;
+const uint JOIN_ROUND = 5u;
+const float MITER_LIMIT = 4.;
+
+// Signed distance to the stroke as seen from one end of the segment, where
+// `q` is relative to that end and the segment runs into it along +x.
+// `neighbor` is the direction of the next segment from the end. Points past
+// the line bisecting a join belong to the next segment, so that translucent
+// strokes don't overlap; `owned` is cleared for those.
+float end_distance(vec2 q, vec2 neighbor, uint kind, float half_width, out bool owned) {
+    owned = true;
+    float body = abs(q.y) - half_width;
+
+    if (kind >= JOIN_MITER) {
+        vec2 m = vec2(1., 0.) + neighbor;
+
+        if (dot(m, m) < 1e-6) {
+            // The path doubles back on itself.
+            kind = kind == JOIN_ROUND ? CAP_ROUND : 0u;
+        } else {
+            m = normalize(m);
+            owned = dot(q, m) <= 0.;
+
+            if (kind == JOIN_ROUND) {
+                return min(max(body, q.x), length(q) - half_width);
+            }
+            if (kind == JOIN_MITER && m.x >= 1. / MITER_LIMIT) {
+                return body;
+            }
+
+            // Bevel: cut the corner between the outer edges of both segments.
+            float side = neighbor.y > 0. ? -1. : 1.;
+            vec2 outer = vec2(0., side);
+            vec2 outer_next = side * vec2(-neighbor.y, neighbor.x);
+            vec2 bevel = normalize(outer + outer_next);
+            return max(body, dot(q, bevel) - half_width * dot(outer, bevel));
+        }
+    }
+
+    if (kind == CAP_SQUARE) {
+        return max(body, q.x - half_width);
+    }
+    if (kind == CAP_ROUND) {
+        return min(max(body, q.x), length(q) - half_width);
+    }
+    return max(body, q.x);
+}
+
+void main() {
+    // Stroke space units per screen pixel, taken before any discard.
+    float pixel = length(vec2(dFdx(v_local.x), dFdy(v_local.x)));
+
+    bool owned_start;
+    bool owned_end;
+    float dist = max(
+        end_distance(-v_local, v_neighbors.xy, v_style & 15u, v_half_width, owned_start),
+        end_distance(v_local - vec2(v_length, 0.), v_neighbors.zw, (v_style >> 4) & 15u, v_half_width, owned_end)
+    );
+
+    float alpha = clamp(0.5 - dist / pixel, 0., 1.);
+    if (!owned_start || !owned_end || alpha <= 0.) {
+        discard;
+    }
+
+#ifdef PICKING
+    f_id = uvec4(u_pick_id, v_instance, 0u, 0u);
+#else
+    vec4 color = vec4(
+        float((v_color & 0x000000FFu)) / 255.,
+        float((v_color & 0x0000FF00u) >> 8) / 255.,
+        float((v_color & 0x00FF0000u) >> 16) / 255.,
+        float((v_color & 0xFF000000u) >> 24) / 255.);
+    color.a *= alpha;
+    f_color = vec4(color.rgb * color.a, color.a);
+#endif
+}
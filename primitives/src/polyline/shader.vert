@@ -0,0 +1,111 @@
+#version 300 es
+
+in vec2 prev;
+in vec2 start;
+in vec2 end;
+in vec2 next;
+in float width;
+in uint color;
+in uint style;
+in uint path;
+in vec2 line_position;
+
+uniform mat4 u_transform;
+uniform vec2 u_resolution;
+
+flat out uint v_color;
+flat out uint v_style;
+flat out float v_length;
+flat out float v_half_width;
+flat out vec4 v_neighbors;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
+out vec2 v_local;
+
+const uint SCREEN_UNITS = 256u;
+const uint JOIN_MITER = 3u;
+const float MITER_LIMIT = 4.;
+
+// The stroke is laid out in "stroke space": data space for widths in world
+// units, or CSS pixels for widths in screen units.
+bool screen_units() {
+    return (style & SCREEN_UNITS) != 0u;
+}
+
+vec2 to_pixels(vec2 point) {
+    vec4 clip = vec4(point, 0., 1.) * u_transform;
+    return (clip.xy / clip.w * 0.5 + 0.5) * u_resolution;
+}
+
+vec2 to_stroke_space(vec2 point) {
+    return screen_units() ? to_pixels(point) : point;
+}
+
+vec2 direction(vec2 from, vec2 to) {
+    vec2 d = to - from;
+    return dot(d, d) > 0. ? normalize(d) : vec2(1., 0.);
+}
+
+// How far the stroke can reach past a segment end, given the direction of
+// the adjacent segment in the segment's local frame.
+float reach(uint kind, vec2 neighbor, float half_width) {
+    if (kind == 0u) {
+        return 0.;
+    }
+    if (kind == JOIN_MITER) {
+        vec2 m = vec2(1., 0.) + neighbor;
+        if (dot(m, m) > 1e-6) {
+            m = normalize(m);
+            if (m.x >= 1. / MITER_LIMIT) {
+                return half_width * max(abs(m.y) / m.x, 1.);
+            }
+        }
+    }
+    return half_width;
+}
+
+void main() {
+    vec2 p = to_stroke_space(prev);
+    vec2 a = to_stroke_space(start);
+    vec2 b = to_stroke_space(end);
+    vec2 n = to_stroke_space(next);
+
+    vec2 d = direction(a, b);
+    vec2 normal = vec2(-d.y, d.x);
+    float len = length(b - a);
+
+    // Directions away from each end along the neighboring segments, in a
+    // frame where this segment runs into that end along +x.
+    vec2 d0 = direction(p, a);
+    vec2 d2 = direction(b, n);
+    v_neighbors = vec4(dot(d0, d), dot(d0, normal), dot(d2, d), dot(d2, normal));
+
+    // One pixel in stroke space, to leave room for anti-aliasing.
+    float pixel = screen_units() ? 1. : 1. / length(to_pixels(start + normal) - to_pixels(start));
+
+    float half_width = width / 2.;
+    float start_extension = reach(style & 15u, v_neighbors.xy, half_width) + pixel;
+    float end_extension = reach((style >> 4) & 15u, v_neighbors.zw, half_width) + pixel;
+
+    v_local = vec2(
+        mix(-start_extension, len + end_extension, line_position.x),
+        line_position.y * (half_width + pixel)
+    );
+    vec2 position = a + d * v_local.x + normal * v_local.y;
+
+    if (screen_units()) {
+        gl_Position = vec4(position / u_resolution * 2. - 1., 0., 1.);
+    } else {
+        gl_Position = vec4(position, 0., 1.) * u_transform;
+    }
+
+    v_length = len;
+    v_half_width = half_width;
+    v_style = style;
+    v_color = color;
+
+#ifdef PICKING
+    v_instance = path;
+#endif
+}
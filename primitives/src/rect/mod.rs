@@ -1,28 +1,125 @@
-use anyhow::Result;
 use limelight::{
     attribute,
+    Attribute,
+    picking::Pickable,
     renderer::Drawable,
-    state::{
-        blending::{BlendFunction, BlendingFactorDest, BlendingFactorSrc},
-        StateDescriptor,
-    },
-    Buffer, BufferUsageHint, DrawMode, Program, Uniform,
+    Buffer, BufferUsageHint, DrawMode, Program, Result, Uniform, UniformSource,
 };
 
-use crate::{color::Color, common::{RectPosition, identity_rect}};
+use crate::{color::Color, hit_test::{to_data_space, HitIndex, HitShape}, common::{sealed::InstanceShaders, layer_program, picking_program, RectPosition, identity_rect}};
 
 #[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     pub upper_left: [f32; 2],
     pub lower_right: [f32; 2],
     pub color: Color,
 }
 
-pub struct RectLayer {
-    rects: Buffer<Rect>,
+impl HitShape for Rect {
+    fn bounds(&self) -> [f32; 4] {
+        let [x0, y0] = self.upper_left;
+        let [x1, y1] = self.lower_right;
+        [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)]
+    }
+
+    fn contains(&self, point: [f32; 2]) -> bool {
+        let [min_x, min_y, max_x, max_y] = self.bounds();
+        (min_x..=max_x).contains(&point[0]) && (min_y..=max_y).contains(&point[1])
+    }
+}
+
+/// A rectangle with rounded corners, an optional border and a rotation,
+/// e.g. for tooltips and legend boxes.
+#[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundedRect {
+    /// The corners before rotation.
+    pub upper_left: [f32; 2],
+    pub lower_right: [f32; 2],
+    pub color: Color,
+
+    /// Radius of the rounded corners, in data units. It is limited to half
+    /// the shorter side.
+    pub corner_radius: f32,
+
+    /// Width of the border drawn inside the edges in `border_color`, in data
+    /// units. Zero for no border.
+    pub border_width: f32,
+    pub border_color: Color,
+
+    /// Counterclockwise rotation around the center, in radians.
+    pub rotation: f32,
+}
+
+impl RoundedRect {
+    fn center(&self) -> [f32; 2] {
+        let [x0, y0] = self.upper_left;
+        let [x1, y1] = self.lower_right;
+        [(x0 + x1) / 2., (y0 + y1) / 2.]
+    }
+
+    fn half_size(&self) -> [f32; 2] {
+        let [x0, y0] = self.upper_left;
+        let [x1, y1] = self.lower_right;
+        [(x1 - x0).abs() / 2., (y1 - y0).abs() / 2.]
+    }
+}
+
+impl HitShape for RoundedRect {
+    fn bounds(&self) -> [f32; 4] {
+        let [cx, cy] = self.center();
+        let [hx, hy] = self.half_size();
+        let (sin, cos) = self.rotation.sin_cos();
+        let (ex, ey) = (
+            hx * cos.abs() + hy * sin.abs(),
+            hx * sin.abs() + hy * cos.abs(),
+        );
+        [cx - ex, cy - ey, cx + ex, cy + ey]
+    }
+
+    /// Mirrors the rounded box distance in the fragment shader.
+    fn contains(&self, point: [f32; 2]) -> bool {
+        let [cx, cy] = self.center();
+        let [hx, hy] = self.half_size();
+        let (sin, cos) = self.rotation.sin_cos();
+        let (dx, dy) = (point[0] - cx, point[1] - cy);
+        let (px, py) = (cos * dx + sin * dy, cos * dy - sin * dx);
+
+        let r = self.corner_radius.clamp(0., hx.min(hy));
+        let (qx, qy) = (px.abs() - hx + r, py.abs() - hy + r);
+        let d = qx.max(0.).hypot(qy.max(0.)) + qx.max(qy).min(0.) - r;
+        d <= 0.
+    }
+}
+
+/// An instance type that a `RectLayer` can draw: `Rect`, or `RoundedRect`
+/// for rounded, bordered and rotated rectangles.
+pub trait RectInstance: Attribute + InstanceShaders {}
+
+impl InstanceShaders for Rect {
+    const VERTEX_SHADER: &'static str = include_str!("shader.vert");
+    const FRAGMENT_SHADER: &'static str = include_str!("shader.frag");
+    const LABEL: &'static str = "rects";
+}
+
+impl RectInstance for Rect {}
+
+impl InstanceShaders for RoundedRect {
+    const VERTEX_SHADER: &'static str = include_str!("rounded.vert");
+    const FRAGMENT_SHADER: &'static str = include_str!("rounded.frag");
+    const LABEL: &'static str = "rounded rects";
+}
+
+impl RectInstance for RoundedRect {}
+
+pub struct RectLayer<T: RectInstance = Rect> {
+    rects: Buffer<T>,
     positions: Buffer<RectPosition>,
-    program: Program<RectPosition, Rect>,
-    transform: Uniform<[[f32; 4]; 4]>,
+    program: Program<RectPosition, T>,
+    picking_program: Program<RectPosition, T>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+    hit_index: HitIndex<T>,
 }
 
 impl Default for RectLayer {
@@ -31,48 +128,122 @@ impl Default for RectLayer {
     }
 }
 
+impl Default for RectLayer<RoundedRect> {
+    fn default() -> Self {
+        RectLayer::rounded()
+    }
+}
+
 impl RectLayer {
+    /// Create a layer with its own identity transform.
     pub fn new() -> Self {
-        Self::new_transform(Uniform::identity())
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
     }
 
+    /// Create a layer with its own transform.
     pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
-        let program = Program::new(
-            include_str!("shader.vert"),
-            include_str!("shader.frag"),
+        Self::new_impl(Some(transform))
+    }
+
+    /// Return the index of the topmost rectangle under `point`, given in clip
+    /// space. The inverse of the layer's transform is applied to the point;
+    /// for layers created with `new_global`, that is the `u_transform`
+    /// supplied by `uniforms`, usually the `Renderer`.
+    pub fn hit_test(&self, uniforms: &impl UniformSource, point: [f32; 2]) -> Option<usize> {
+        let point = to_data_space(&self.transform, uniforms, point)?;
+        self.hit_index.hit_test(&self.rects, point)
+    }
+}
+
+impl RectLayer<RoundedRect> {
+    /// Create a layer of `RoundedRect`s with its own identity transform.
+    pub fn rounded() -> Self {
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer of `RoundedRect`s that uses the renderer's global
+    /// `u_transform`, like `RectLayer::new_global`.
+    pub fn rounded_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer of `RoundedRect`s with its own transform, which takes
+    /// precedence over the renderer's global `u_transform`.
+    pub fn rounded_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(Some(transform))
+    }
+
+    /// Return the index of the topmost rectangle under `point`, given in
+    /// clip space, as for `RectLayer::hit_test`. Rounded corners and the
+    /// rotation are taken into account.
+    pub fn hit_test(&self, uniforms: &impl UniformSource, point: [f32; 2]) -> Option<usize> {
+        let point = to_data_space(&self.transform, uniforms, point)?;
+        self.hit_index.hit_test(&self.rects, point)
+    }
+}
+
+impl<T: RectInstance> RectLayer<T> {
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let program = layer_program(
+            T::VERTEX_SHADER,
+            T::FRAGMENT_SHADER,
+            T::LABEL,
             DrawMode::TriangleStrip,
-        )
-        .with_state(StateDescriptor {
-            blend_func: Some(BlendFunction {
-                source_factor: BlendingFactorSrc::One,
-                dst_factor: BlendingFactorDest::OneMinusSrcAlpha,
-                ..Default::default()
-            }),
-            ..Default::default()
-        })
-        .with_uniform("u_transform", transform.clone());
+            &transform,
+        );
 
         RectLayer {
             rects: Buffer::new_empty(BufferUsageHint::DynamicDraw),
             positions: Buffer::new(identity_rect(), BufferUsageHint::StaticDraw),
             program,
+            picking_program: picking_program(
+                T::VERTEX_SHADER,
+                T::FRAGMENT_SHADER,
+                T::LABEL,
+                &transform,
+            ),
             transform,
+            hit_index: HitIndex::default(),
         }
     }
 
-    pub fn transform(&self) -> Uniform<[[f32; 4]; 4]> {
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
         self.transform.clone()
     }
 
-    pub fn buffer(&self) -> Buffer<Rect> {
+    pub fn buffer(&self) -> Buffer<T> {
         self.rects.clone()
     }
+
+    /// Copy the rects currently in the layer's buffer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<T> {
+        self.rects.data()
+    }
+
+    /// Replace the rects in the layer's buffer with previously exported ones.
+    pub fn import(&self, data: Vec<T>) {
+        self.rects.set_data(data);
+    }
 }
 
-impl Drawable for RectLayer {
+impl<T: RectInstance> Drawable for RectLayer<T> {
     fn draw(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
         renderer.render_instanced(&mut self.program, &self.positions, &self.rects)?;
 
         Ok(())
     }
 }
+
+impl<T: RectInstance> Pickable for RectLayer<T> {
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.picking_program, &self.positions, &self.rects)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+#version 300 es
+
+precision highp float;
+
+flat in uint v_color;
+flat in uint v_border_color;
+flat in float v_corner_radius;
+flat in float v_border_width;
+flat in vec2 v_half_size;
+in vec2 v_coord;
+#ifdef PICKING
+flat in uint v_instance;
+uniform uint u_pick_id;
+out uvec4 f_id;
+#else
+out vec4 f_color;
+#endif
+
+vec4 unpack_color(uint color) {
+    return vec4(
+        float((color & 0x000000FFu)) / 255.,
+        float((color & 0x0000FF00u) >> 8) / 255.,
+        float((color & 0x00FF0000u) >> 16) / 255.,
+        float((color & 0xFF000000u) >> 24) / 255.);
+}
+
+// Signed distance to the edge of the rounded rect, negative inside.
+float rect_distance(vec2 p) {
+    vec2 q = abs(p) - v_half_size + v_corner_radius;
+    return length(max(q, 0.)) + min(max(q.x, q.y), 0.) - v_corner_radius;
+}
+
+void main() {
+    float d = rect_distance(v_coord);
+    float delta = fwidth(d);
+
+    float alpha = 1.0 - smoothstep(-delta, 0., d);
+
+    if (alpha < 0.01) {
+        discard;
+    }
+
+#ifdef PICKING
+    f_id = uvec4(u_pick_id, v_instance, 0u, 0u);
+#else
+    vec4 color = unpack_color(v_color);
+    if (v_border_width > 0.) {
+        float border = smoothstep(-v_border_width - delta, -v_border_width, d);
+        color = mix(color, unpack_color(v_border_color), border);
+    }
+
+    f_color = color * alpha;
+#endif
+}
@@ -0,0 +1,45 @@
+#version 300 es
+
+in vec2 upper_left;
+in vec2 lower_right;
+in uint color;
+in float corner_radius;
+in float border_width;
+in uint border_color;
+in float rotation;
+in vec2 rect_position;
+
+flat out uint v_color;
+flat out uint v_border_color;
+flat out float v_corner_radius;
+flat out float v_border_width;
+flat out vec2 v_half_size;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
+// Offset from the center of the rect, before rotation, in data units.
+out vec2 v_coord;
+
+uniform mat4 u_transform;
+
+void main() {
+    vec2 center = (upper_left + lower_right) / 2.;
+    vec2 offset = mix(upper_left, lower_right, rect_position) - center;
+    vec2 rotated = vec2(
+        cos(rotation) * offset.x - sin(rotation) * offset.y,
+        sin(rotation) * offset.x + cos(rotation) * offset.y
+    );
+
+    gl_Position = vec4(center + rotated, 0., 1.) * u_transform;
+
+    v_half_size = abs(lower_right - upper_left) / 2.;
+    v_corner_radius = clamp(corner_radius, 0., min(v_half_size.x, v_half_size.y));
+    v_border_width = border_width;
+    v_coord = offset;
+    v_color = color;
+    v_border_color = border_color;
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
+}
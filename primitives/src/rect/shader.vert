@@ -6,6 +6,9 @@ in uint color;
 in vec2 rect_position;
 
 flat out uint v_color;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
 uniform mat4 u_transform;
 
 void main() {
@@ -19,4 +22,8 @@ void main() {
     gl_Position = gl_Position * u_transform;
 
     v_color = color;
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
 }
\ No newline at end of file
@@ -0,0 +1,99 @@
+use crate::packer::ShelfPacker;
+use limelight::{Error, PixelFormat, Result, Texture};
+
+/// Transparent pixels left around each image, so that filtering at its
+/// edges doesn't pick up its neighbours.
+const PADDING: u32 = 1;
+
+/// Packs RGBA images into one texture, for a `SpriteLayer` to draw from.
+pub struct SpriteAtlas {
+    width: u32,
+    height: u32,
+    packer: ShelfPacker,
+    pixels: Vec<u8>,
+    texture: Texture,
+
+    /// Set when images are added, until the pixels are uploaded.
+    dirty: bool,
+}
+
+impl SpriteAtlas {
+    /// Create an empty atlas of `width` x `height` pixels.
+    pub fn new(width: u32, height: u32) -> Self {
+        SpriteAtlas {
+            width,
+            height,
+            packer: ShelfPacker::new(width, height),
+            pixels: vec![0; (width * height * 4) as usize],
+            texture: Texture::new(width as _, height as _, PixelFormat::Rgba8),
+            dirty: false,
+        }
+    }
+
+    /// Add a `width` x `height` image of (non-premultiplied) RGBA pixels,
+    /// with rows from the top down, and return its `Sprite::uv` rect.
+    pub fn add(&mut self, width: u32, height: u32, pixels: &[u8]) -> Result<[f32; 4]> {
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidUsage(format!(
+                "Can't add an empty {}x{} image to a sprite atlas.",
+                width, height
+            )));
+        }
+
+        if pixels.len() != (width * height * 4) as usize {
+            return Err(Error::InvalidUsage(format!(
+                "Expected {} bytes for a {}x{} image, got {}.",
+                width * height * 4,
+                width,
+                height,
+                pixels.len()
+            )));
+        }
+
+        let (x, y) = self
+            .packer
+            .pack(width + 2 * PADDING, height + 2 * PADDING)
+            .ok_or_else(|| {
+                Error::InvalidUsage(format!(
+                    "The sprite atlas has no room left for a {}x{} image.",
+                    width, height
+                ))
+            })?;
+        let (x, y) = (x + PADDING, y + PADDING);
+
+        // Premultiply, so that filtering doesn't darken translucent edges.
+        for (row, source) in pixels.chunks_exact((width * 4) as usize).enumerate() {
+            let start = (((y + row as u32) * self.width + x) * 4) as usize;
+            let target = &mut self.pixels[start..start + source.len()];
+            for (target, source) in target.chunks_exact_mut(4).zip(source.chunks_exact(4)) {
+                let alpha = source[3] as u32;
+                for channel in 0..3 {
+                    target[channel] = ((source[channel] as u32 * alpha + 127) / 255) as u8;
+                }
+                target[3] = source[3];
+            }
+        }
+        self.dirty = true;
+
+        // Rows are uploaded from the top, so the first is at v = 0.
+        let (atlas_width, atlas_height) = (self.width as f32, self.height as f32);
+        Ok([
+            x as f32 / atlas_width,
+            y as f32 / atlas_height,
+            (x + width) as f32 / atlas_width,
+            (y + height) as f32 / atlas_height,
+        ])
+    }
+
+    /// The atlas texture, with any images added since the last call
+    /// uploaded. Layers created with an earlier copy of the texture see the
+    /// new images too.
+    pub fn texture(&mut self) -> Result<Texture> {
+        if self.dirty {
+            self.texture.set_data(&self.pixels)?;
+            self.dirty = false;
+        }
+
+        Ok(self.texture.clone())
+    }
+}
@@ -0,0 +1,136 @@
+mod atlas;
+
+pub use self::atlas::SpriteAtlas;
+use crate::{color::Color, common::{layer_program, picking_program, RectPosition, Units, identity_rect}};
+use limelight::{
+    attribute,
+    picking::Pickable,
+    renderer::Drawable,
+    Buffer, BufferUsageHint, DrawMode, Program, Result, Texture, Uniform,
+};
+
+/// A textured quad, drawn from a region of the layer's texture.
+#[attribute]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sprite {
+    /// The center of the quad.
+    pub position: [f32; 2],
+
+    /// Width and height, in the layer's units.
+    pub size: [f32; 2],
+
+    /// Counterclockwise rotation around the center, in radians.
+    pub rotation: f32,
+
+    /// Left, top, right and bottom texture coordinates of the image, as
+    /// returned by `SpriteAtlas::add`.
+    pub uv: [f32; 4],
+
+    /// Multiplied with the texture's color; white leaves it unchanged.
+    pub tint: Color,
+}
+
+pub struct SpriteLayer {
+    sprites: Buffer<Sprite>,
+    positions: Buffer<RectPosition>,
+    program: Program<RectPosition, Sprite>,
+    picking_program: Program<RectPosition, Sprite>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+
+    /// 1 if sprite sizes are in CSS pixels, 0 if they are in data units.
+    screen_units: Uniform<u32>,
+}
+
+impl SpriteLayer {
+    /// Create a layer that draws from `texture`, e.g. a `SpriteAtlas`'s,
+    /// with its own identity transform.
+    pub fn new(texture: &Texture) -> Self {
+        Self::new_impl(texture, Some(Uniform::identity()))
+    }
+
+    /// Create a layer that draws from `texture` with the renderer's global `u_transform`.
+    pub fn new_global(texture: &Texture) -> Self {
+        Self::new_impl(texture, None)
+    }
+
+    /// Create a layer with its own transform.
+    pub fn new_transform(texture: &Texture, transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(texture, Some(transform))
+    }
+
+    fn new_impl(texture: &Texture, transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let screen_units = Uniform::new(0);
+
+        let program = layer_program(
+            include_str!("shader.vert"),
+            include_str!("shader.frag"),
+            "sprites",
+            DrawMode::TriangleStrip,
+            &transform,
+        )
+        .with_texture("u_texture", texture)
+        .with_uniform("u_screen_units", screen_units.clone());
+
+        let picking_program = picking_program(
+            include_str!("shader.vert"),
+            include_str!("shader.frag"),
+            "sprites",
+            &transform,
+        )
+        .with_texture("u_texture", texture)
+        .with_uniform("u_screen_units", screen_units.clone());
+
+        SpriteLayer {
+            sprites: Buffer::new_empty(BufferUsageHint::DynamicDraw),
+            positions: Buffer::new(identity_rect(), BufferUsageHint::StaticDraw),
+            program,
+            picking_program,
+            transform,
+            screen_units,
+        }
+    }
+
+    /// Set the units of sprite sizes. With `Units::Screen`, sprites
+    /// stay the same size on screen while zooming, like icons and map
+    /// markers. The default is `Units::World`.
+    pub fn with_units(self, units: Units) -> Self {
+        self.screen_units.set_value((units == Units::Screen) as u32);
+        self
+    }
+
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
+        self.transform.clone()
+    }
+
+    pub fn buffer(&self) -> Buffer<Sprite> {
+        self.sprites.clone()
+    }
+
+    /// Copy the sprites currently in the layer's buffer, e.g. to save a scene.
+    pub fn export(&self) -> Vec<Sprite> {
+        self.sprites.data()
+    }
+
+    /// Replace the sprites in the layer's buffer with previously exported ones.
+    pub fn import(&self, data: Vec<Sprite>) {
+        self.sprites.set_data(data);
+    }
+}
+
+impl Drawable for SpriteLayer {
+    fn draw(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.program, &self.positions, &self.sprites)?;
+
+        Ok(())
+    }
+}
+
+impl Pickable for SpriteLayer {
+    /// Transparent parts of a sprite's image can't be picked.
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        renderer.render_instanced(&mut self.picking_program, &self.positions, &self.sprites)?;
+
+        Ok(())
+    }
+}
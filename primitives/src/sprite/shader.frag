@@ -0,0 +1,35 @@
+#version 300 es
+
+precision highp float;
+
+flat in uint v_tint;
+#ifdef PICKING
+flat in uint v_instance;
+uniform uint u_pick_id;
+out uvec4 f_id;
+#else
+out vec4 f_color;
+#endif
+in vec2 v_uv;
+
+// Premultiplied RGBA.
+uniform sampler2D u_texture;
+
+void main() {
+    vec4 texel = texture(u_texture, v_uv);
+
+    if (texel.a < 0.01) {
+        discard;
+    }
+
+#ifdef PICKING
+    f_id = uvec4(u_pick_id, v_instance, 0u, 0u);
+#else
+    vec4 tint = vec4(
+        float((v_tint & 0x000000FFu)) / 255.,
+        float((v_tint & 0x0000FF00u) >> 8) / 255.,
+        float((v_tint & 0x00FF0000u) >> 16) / 255.,
+        float((v_tint & 0xFF000000u) >> 24) / 255.);
+    f_color = texel * vec4(tint.rgb * tint.a, tint.a);
+#endif
+}
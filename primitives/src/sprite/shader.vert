@@ -0,0 +1,40 @@
+#version 300 es
+
+in vec2 position;
+in vec2 size;
+in float rotation;
+in vec4 uv;
+in uint tint;
+in vec2 rect_position;
+
+flat out uint v_tint;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
+out vec2 v_uv;
+
+uniform mat4 u_transform;
+uniform vec2 u_resolution;
+uniform uint u_screen_units;
+
+void main() {
+    vec2 corner = (rect_position - 0.5) * size;
+    float c = cos(rotation);
+    float s = sin(rotation);
+    corner = vec2(c * corner.x - s * corner.y, s * corner.x + c * corner.y);
+
+    if (u_screen_units == 1u) {
+        gl_Position = vec4(position, 0., 1.) * u_transform;
+        gl_Position.xy += corner * 2. / u_resolution * gl_Position.w;
+    } else {
+        gl_Position = vec4(position + corner, 0., 1.) * u_transform;
+    }
+
+    // The top of the quad (rect_position.y = 1) shows the top of the image.
+    v_uv = vec2(mix(uv.x, uv.z, rect_position.x), mix(uv.w, uv.y, rect_position.y));
+    v_tint = tint;
+
+#ifdef PICKING
+    v_instance = uint(gl_InstanceID);
+#endif
+}
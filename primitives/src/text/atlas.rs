@@ -0,0 +1,342 @@
+use crate::packer::ShelfPacker;
+use limelight::{Error, Result};
+use std::collections::HashMap;
+use wasm_bindgen::JsCast;
+use web_sys::CanvasRenderingContext2d;
+
+/// The width and height of the atlas texture, in pixels.
+pub(crate) const ATLAS_SIZE: u32 = 1024;
+
+/// The font size glyphs are rasterized at, in pixels.
+pub(crate) const RASTER_SIZE: f32 = 32.;
+
+/// Padding around each glyph, so that the distance field can extend past
+/// its outline.
+const BUFFER: u32 = 4;
+
+/// How many pixels away from the outline the distance field reaches.
+const RADIUS: f64 = 8.;
+
+/// Where the outline falls in the distance field, as a fraction of its range
+/// from the top. The text shader draws pixels above `1 - CUTOFF`.
+const CUTOFF: f64 = 0.25;
+
+/// A coverage bitmap of a single glyph, along with its placement relative to
+/// the pen position on the baseline. Distances are in pixels, with y up.
+pub struct RasterizedGlyph {
+    pub width: u32,
+    pub height: u32,
+
+    /// The horizontal distance from the pen position to the bitmap's left edge.
+    pub left: f32,
+
+    /// The distance from the baseline up to the bitmap's top edge.
+    pub top: f32,
+
+    /// How far to move the pen for the next glyph.
+    pub advance: f32,
+
+    /// `width * height` coverage values, row by row from the top.
+    pub alpha: Vec<u8>,
+}
+
+/// Vertical extents of a font, in pixels from the baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontMetrics {
+    pub ascent: f32,
+    pub descent: f32,
+}
+
+/// A source of glyph bitmaps for a `TextLayer`.
+pub trait GlyphRasterizer {
+    fn rasterize(&mut self, ch: char, font_size: f32) -> Result<RasterizedGlyph>;
+
+    fn font_metrics(&mut self, font_size: f32) -> Result<FontMetrics>;
+}
+
+/// Rasterizes glyphs by drawing them to an offscreen 2D canvas, so any font
+/// available to the page can be used.
+pub struct CanvasRasterizer {
+    family: String,
+    context: Option<CanvasRenderingContext2d>,
+}
+
+impl CanvasRasterizer {
+    /// `family` is a CSS font family list, like `"Inter, sans-serif"`.
+    pub fn new(family: &str) -> Self {
+        CanvasRasterizer {
+            family: family.to_string(),
+            context: None,
+        }
+    }
+
+    fn context(&mut self, font_size: f32) -> Result<&CanvasRenderingContext2d> {
+        if self.context.is_none() {
+            let document = web_sys::window()
+                .and_then(|window| window.document())
+                .ok_or_else(|| Error::InvalidUsage("CanvasRasterizer requires a document.".to_string()))?;
+            let canvas: web_sys::HtmlCanvasElement = document
+                .create_element("canvas")
+                .map_err(js_error)?
+                .dyn_into()
+                .map_err(js_error)?;
+            let size = (2. * RASTER_SIZE) as u32;
+            canvas.set_width(size);
+            canvas.set_height(size);
+
+            let context = canvas
+                .get_context("2d")
+                .map_err(js_error)?
+                .ok_or_else(|| Error::InvalidUsage("Can't create a 2D canvas context.".to_string()))?
+                .dyn_into()
+                .map_err(js_error)?;
+            self.context = Some(context);
+        }
+
+        let context = self.context.as_ref().unwrap();
+        context.set_font(&format!("{}px {}", font_size, self.family));
+        context.set_text_baseline("alphabetic");
+        context.set_fill_style_str("black");
+
+        Ok(context)
+    }
+}
+
+fn js_error(value: impl std::fmt::Debug) -> Error {
+    Error::Js(format!("{:?}", value))
+}
+
+impl GlyphRasterizer for CanvasRasterizer {
+    fn rasterize(&mut self, ch: char, font_size: f32) -> Result<RasterizedGlyph> {
+        let context = self.context(font_size)?;
+        let canvas_size = (2. * RASTER_SIZE) as f64;
+        let text = ch.to_string();
+        let metrics = context.measure_text(&text).map_err(js_error)?;
+
+        let left = metrics.actual_bounding_box_left().ceil();
+        let ascent = metrics.actual_bounding_box_ascent().ceil();
+        let width = (left + metrics.actual_bounding_box_right().ceil()).clamp(0., canvas_size);
+        let height = (ascent + metrics.actual_bounding_box_descent().ceil()).clamp(0., canvas_size);
+
+        let mut alpha = Vec::new();
+        if width > 0. && height > 0. {
+            context.clear_rect(0., 0., canvas_size, canvas_size);
+            context.fill_text(&text, left, ascent).map_err(js_error)?;
+            let image = context
+                .get_image_data(0., 0., width, height)
+                .map_err(js_error)?;
+            alpha = image.data().0.chunks(4).map(|pixel| pixel[3]).collect();
+        }
+
+        Ok(RasterizedGlyph {
+            width: width as u32,
+            height: height as u32,
+            left: -left as f32,
+            top: ascent as f32,
+            advance: metrics.width() as f32,
+            alpha,
+        })
+    }
+
+    fn font_metrics(&mut self, font_size: f32) -> Result<FontMetrics> {
+        let metrics = self.context(font_size)?.measure_text("Mg").map_err(js_error)?;
+
+        Ok(FontMetrics {
+            ascent: metrics.font_bounding_box_ascent() as f32,
+            descent: metrics.font_bounding_box_descent() as f32,
+        })
+    }
+}
+
+/// A glyph's location in the atlas and its placement, in pixels at
+/// `RASTER_SIZE`, including the padding around it.
+#[derive(Clone, Copy)]
+pub(crate) struct AtlasGlyph {
+    pub rect: [u32; 4],
+    pub left: f32,
+    pub top: f32,
+    pub advance: f32,
+}
+
+/// Signed distance fields of the glyphs used so far, packed into one
+/// single-channel image. Glyphs are added as text using them is laid out.
+pub(crate) struct GlyphAtlas {
+    rasterizer: Box<dyn GlyphRasterizer>,
+    glyphs: HashMap<char, AtlasGlyph>,
+    font_metrics: Option<FontMetrics>,
+    packer: ShelfPacker,
+    pub pixels: Vec<u8>,
+
+    /// Set when glyphs are added, until the pixels are uploaded.
+    pub dirty: bool,
+}
+
+impl GlyphAtlas {
+    pub fn new(rasterizer: Box<dyn GlyphRasterizer>) -> Self {
+        GlyphAtlas {
+            rasterizer,
+            glyphs: HashMap::new(),
+            font_metrics: None,
+            packer: ShelfPacker::new(ATLAS_SIZE, ATLAS_SIZE),
+            pixels: vec![0; (ATLAS_SIZE * ATLAS_SIZE) as usize],
+            dirty: false,
+        }
+    }
+
+    pub fn font_metrics(&mut self) -> Result<FontMetrics> {
+        if let Some(metrics) = self.font_metrics {
+            return Ok(metrics);
+        }
+
+        let metrics = self.rasterizer.font_metrics(RASTER_SIZE)?;
+        self.font_metrics = Some(metrics);
+        Ok(metrics)
+    }
+
+    pub fn glyph(&mut self, ch: char) -> Result<AtlasGlyph> {
+        if let Some(glyph) = self.glyphs.get(&ch) {
+            return Ok(*glyph);
+        }
+
+        let raster = self.rasterizer.rasterize(ch, RASTER_SIZE)?;
+        let width = raster.width + 2 * BUFFER;
+        let height = raster.height + 2 * BUFFER;
+
+        let (x, y) = self.packer.pack(width, height).ok_or_else(|| {
+            Error::InvalidUsage(format!("The glyph atlas has no room left for {:?}.", ch))
+        })?;
+
+        let sdf = signed_distance_field(&raster, width, height);
+        for row in 0..height {
+            let start = ((y + row) * ATLAS_SIZE + x) as usize;
+            self.pixels[start..start + width as usize]
+                .copy_from_slice(&sdf[(row * width) as usize..((row + 1) * width) as usize]);
+        }
+        self.dirty = true;
+
+        let glyph = AtlasGlyph {
+            rect: [x, y, width, height],
+            left: raster.left - BUFFER as f32,
+            top: raster.top + BUFFER as f32,
+            advance: raster.advance,
+        };
+        self.glyphs.insert(ch, glyph);
+
+        Ok(glyph)
+    }
+}
+
+const INF: f64 = 1e20;
+
+/// Compute a distance field for a glyph with `BUFFER` pixels of padding, as
+/// bytes where the outline is at `255 * (1 - CUTOFF)` and values fall off
+/// by `255 / RADIUS` per pixel away from it.
+fn signed_distance_field(raster: &RasterizedGlyph, width: u32, height: u32) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+
+    // Squared distances to the nearest pixel outside and inside the glyph.
+    let mut outer = vec![INF; width * height];
+    let mut inner = vec![0.; width * height];
+
+    for y in 0..raster.height as usize {
+        for x in 0..raster.width as usize {
+            let a = raster.alpha[y * raster.width as usize + x] as f64 / 255.;
+            let i = (y + BUFFER as usize) * width + x + BUFFER as usize;
+
+            if a == 1. {
+                outer[i] = 0.;
+                inner[i] = INF;
+            } else if a > 0. {
+                // Approximate the distance to the outline within edge pixels.
+                let d = 0.5 - a;
+                outer[i] = if d > 0. { d * d } else { 0. };
+                inner[i] = if d < 0. { d * d } else { 0. };
+            }
+        }
+    }
+
+    euclidean_distance_transform(&mut outer, width, height);
+    euclidean_distance_transform(&mut inner, width, height);
+
+    outer
+        .iter()
+        .zip(&inner)
+        .map(|(outer, inner)| {
+            let d = outer.sqrt() - inner.sqrt();
+            (255. - 255. * (d / RADIUS + CUTOFF)).round().clamp(0., 255.) as u8
+        })
+        .collect()
+}
+
+/// Felzenszwalb and Huttenlocher's squared distance transform, run over the
+/// columns and then the rows of `grid`.
+fn euclidean_distance_transform(grid: &mut [f64], width: usize, height: usize) {
+    let length = width.max(height);
+    let mut f = vec![0.; length];
+    let mut v = vec![0; length];
+    let mut z = vec![0.; length + 1];
+
+    for x in 0..width {
+        distance_transform_1d(grid, x, width, height, &mut f, &mut v, &mut z);
+    }
+    for y in 0..height {
+        distance_transform_1d(grid, y * width, 1, width, &mut f, &mut v, &mut z);
+    }
+}
+
+fn distance_transform_1d(
+    grid: &mut [f64],
+    offset: usize,
+    stride: usize,
+    length: usize,
+    f: &mut [f64],
+    v: &mut [usize],
+    z: &mut [f64],
+) {
+    v[0] = 0;
+    z[0] = -INF;
+    z[1] = INF;
+    f[0] = grid[offset];
+
+    // Find the lower envelope of the parabolas rooted at each sample.
+    let mut k = 0;
+    for q in 1..length {
+        f[q] = grid[offset + q * stride];
+        let q2 = (q * q) as f64;
+
+        let mut s;
+        loop {
+            let r = v[k];
+            s = (f[q] - f[r] + q2 - (r * r) as f64) / (q - r) as f64 / 2.;
+            if s <= z[k] && k > 0 {
+                k -= 1;
+            } else {
+                break;
+            }
+        }
+
+        if s <= z[k] {
+            // Only reachable with k == 0: the new parabola replaces it.
+            v[0] = q;
+            z[0] = -INF;
+            z[1] = INF;
+            continue;
+        }
+
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = INF;
+    }
+
+    // Sample the envelope.
+    k = 0;
+    for q in 0..length {
+        while z[k + 1] < q as f64 {
+            k += 1;
+        }
+        let r = v[k];
+        let qr = q as f64 - r as f64;
+        grid[offset + q * stride] = f[r] + qr * qr;
+    }
+}
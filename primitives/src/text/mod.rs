@@ -0,0 +1,293 @@
+mod atlas;
+
+use self::atlas::{GlyphAtlas, ATLAS_SIZE, RASTER_SIZE};
+pub use self::atlas::{CanvasRasterizer, FontMetrics, GlyphRasterizer, RasterizedGlyph};
+use crate::{color::Color, common::{layer_program, picking_program, RectPosition, identity_rect}};
+use limelight::{
+    attribute,
+    picking::Pickable,
+    renderer::Drawable,
+    Buffer, BufferUsageHint, DrawMode, PixelFormat, Program, Result, Texture, Uniform,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HorizontalAnchor {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VerticalAnchor {
+    /// The top of the font's ascent.
+    Top,
+    /// Halfway between the top of the ascent and the bottom of the descent.
+    Middle,
+    #[default]
+    Baseline,
+    /// The bottom of the font's descent.
+    Bottom,
+}
+
+/// Which point of a text's bounding box is placed at its position, and
+/// rotated around.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Anchor {
+    pub horizontal: HorizontalAnchor,
+    pub vertical: VerticalAnchor,
+}
+
+/// A single line of text.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Text {
+    pub text: String,
+
+    /// The position of the anchor point, transformed like other layers'
+    /// positions.
+    pub position: [f32; 2],
+
+    /// The font size in CSS pixels, which doesn't change with the transform.
+    pub size: f32,
+    pub color: Color,
+    pub anchor: Anchor,
+
+    /// Counterclockwise rotation around the anchor point, in radians.
+    pub rotation: f32,
+}
+
+/// The size of a line of text, in CSS pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextMetrics {
+    pub width: f32,
+    pub ascent: f32,
+    pub descent: f32,
+}
+
+/// One quad of the glyph instance buffer. Quads are positioned in CSS
+/// pixels relative to the text's anchor point, before rotation.
+#[attribute]
+struct GlyphQuad {
+    anchor: [f32; 2],
+
+    /// Left, bottom, right and top of the quad.
+    quad: [f32; 4],
+
+    /// Atlas texture coordinates of the quad's lower left and upper right.
+    uv: [f32; 4],
+    rotation: f32,
+    color: Color,
+
+    /// The index of the text the glyph belongs to, for picking.
+    text: u32,
+}
+
+pub struct TextLayer {
+    texts: Vec<Text>,
+
+    /// Set when `texts` changes, until the glyph quads are rebuilt.
+    dirty: bool,
+    atlas: GlyphAtlas,
+    texture: Texture,
+    glyphs: Buffer<GlyphQuad>,
+    positions: Buffer<RectPosition>,
+    program: Program<RectPosition, GlyphQuad>,
+    picking_program: Program<RectPosition, GlyphQuad>,
+    transform: Option<Uniform<[[f32; 4]; 4]>>,
+}
+
+impl Default for TextLayer {
+    fn default() -> Self {
+        TextLayer::new()
+    }
+}
+
+impl TextLayer {
+    /// Create a layer that draws in the page's `sans-serif` font, with its
+    /// own identity transform.
+    pub fn new() -> Self {
+        Self::new_impl(Some(Uniform::identity()))
+    }
+
+    /// Create a layer that draws with the renderer's global `u_transform`.
+    pub fn new_global() -> Self {
+        Self::new_impl(None)
+    }
+
+    /// Create a layer with its own transform.
+    pub fn new_transform(transform: Uniform<[[f32; 4]; 4]>) -> Self {
+        Self::new_impl(Some(transform))
+    }
+
+    fn new_impl(transform: Option<Uniform<[[f32; 4]; 4]>>) -> Self {
+        let texture = Texture::new(ATLAS_SIZE as _, ATLAS_SIZE as _, PixelFormat::R8);
+
+        let program = layer_program(
+            include_str!("shader.vert"),
+            include_str!("shader.frag"),
+            "text",
+            DrawMode::TriangleStrip,
+            &transform,
+        )
+        .with_texture("u_atlas", &texture);
+
+        let picking_program = picking_program(
+            include_str!("shader.vert"),
+            include_str!("shader.frag"),
+            "text",
+            &transform,
+        )
+        .with_texture("u_atlas", &texture);
+
+        TextLayer {
+            texts: Vec::new(),
+            dirty: false,
+            atlas: GlyphAtlas::new(Box::new(CanvasRasterizer::new("sans-serif"))),
+            texture,
+            glyphs: Buffer::new_empty(BufferUsageHint::DynamicDraw),
+            positions: Buffer::new(identity_rect(), BufferUsageHint::StaticDraw),
+            program,
+            picking_program,
+            transform,
+        }
+    }
+
+    /// Draw with glyphs from `rasterizer`, such as a `CanvasRasterizer` for
+    /// another font family.
+    pub fn with_font(mut self, rasterizer: impl GlyphRasterizer + 'static) -> Self {
+        self.atlas = GlyphAtlas::new(Box::new(rasterizer));
+        self.dirty = true;
+        self
+    }
+
+    /// The texts currently in the layer.
+    pub fn export(&self) -> Vec<Text> {
+        self.texts.clone()
+    }
+
+    /// Replace the texts in the layer.
+    pub fn import(&mut self, texts: Vec<Text>) {
+        self.texts = texts;
+        self.dirty = true;
+    }
+
+    /// Measure a line of text at the given font size, for layout.
+    pub fn measure(&mut self, text: &str, size: f32) -> Result<TextMetrics> {
+        measure(&mut self.atlas, text, size)
+    }
+
+    /// The layer's own transform, or `None` if it was created with `new_global`.
+    pub fn transform(&self) -> Option<Uniform<[[f32; 4]; 4]>> {
+        self.transform.clone()
+    }
+
+    /// Lay out glyph quads for the current texts, adding any new glyphs to
+    /// the atlas, and upload whatever changed.
+    fn update(&mut self) -> Result<()> {
+        if self.dirty {
+            let mut quads = Vec::new();
+            for (index, text) in self.texts.iter().enumerate() {
+                layout(&mut self.atlas, index, text, &mut quads)?;
+            }
+
+            self.glyphs.set_data(quads);
+            self.dirty = false;
+        }
+
+        if self.atlas.dirty {
+            self.texture.set_data(&self.atlas.pixels)?;
+            self.atlas.dirty = false;
+        }
+
+        Ok(())
+    }
+}
+
+fn measure(atlas: &mut GlyphAtlas, text: &str, size: f32) -> Result<TextMetrics> {
+    let scale = size / RASTER_SIZE;
+    let font = atlas.font_metrics()?;
+
+    let mut width = 0.;
+    for ch in text.chars() {
+        width += atlas.glyph(ch)?.advance;
+    }
+
+    Ok(TextMetrics {
+        width: width * scale,
+        ascent: font.ascent * scale,
+        descent: font.descent * scale,
+    })
+}
+
+/// Append quads for each glyph of `text`, in CSS pixels at its font size.
+fn layout(atlas: &mut GlyphAtlas, index: usize, text: &Text, quads: &mut Vec<GlyphQuad>) -> Result<()> {
+    let metrics = measure(atlas, &text.text, RASTER_SIZE)?;
+    let scale = text.size / RASTER_SIZE;
+
+    let dx = match text.anchor.horizontal {
+        HorizontalAnchor::Left => 0.,
+        HorizontalAnchor::Center => metrics.width / 2.,
+        HorizontalAnchor::Right => metrics.width,
+    };
+    let dy = match text.anchor.vertical {
+        VerticalAnchor::Top => metrics.ascent,
+        VerticalAnchor::Middle => (metrics.ascent - metrics.descent) / 2.,
+        VerticalAnchor::Baseline => 0.,
+        VerticalAnchor::Bottom => -metrics.descent,
+    };
+
+    let atlas_size = ATLAS_SIZE as f32;
+    let mut pen = 0.;
+    for ch in text.text.chars() {
+        let glyph = atlas.glyph(ch)?;
+        let [x, y, width, height] = glyph.rect.map(|v| v as f32);
+
+        if width > 0. && height > 0. {
+            let left = pen + glyph.left - dx;
+            let top = glyph.top - dy;
+
+            quads.push(GlyphQuad {
+                anchor: text.position,
+                quad: [left, top - height, left + width, top].map(|v| v * scale),
+                // Atlas rows are stored from the top, and the first row
+                // uploaded is at v = 0.
+                uv: [
+                    x / atlas_size,
+                    (y + height) / atlas_size,
+                    (x + width) / atlas_size,
+                    y / atlas_size,
+                ],
+                rotation: text.rotation,
+                color: text.color,
+                text: index as u32,
+            });
+        }
+
+        pen += glyph.advance;
+    }
+
+    Ok(())
+}
+
+impl Drawable for TextLayer {
+    fn draw(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        self.update()?;
+        renderer.render_instanced(&mut self.program, &self.positions, &self.glyphs)?;
+
+        Ok(())
+    }
+}
+
+impl Pickable for TextLayer {
+    /// Picks report the index of the text, rather than of the glyph.
+    fn draw_picking(&mut self, renderer: &mut limelight::Renderer) -> Result<()> {
+        self.update()?;
+        renderer.render_instanced(&mut self.picking_program, &self.positions, &self.glyphs)?;
+
+        Ok(())
+    }
+}
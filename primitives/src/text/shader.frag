@@ -0,0 +1,40 @@
+#version 300 es
+
+precision highp float;
+
+flat in uint v_color;
+#ifdef PICKING
+flat in uint v_instance;
+uniform uint u_pick_id;
+out uvec4 f_id;
+#else
+out vec4 f_color;
+#endif
+in vec2 v_uv;
+
+uniform sampler2D u_atlas;
+
+// Where the glyph outline falls in the distance field.
+const float EDGE = 0.75;
+
+void main() {
+    float dist = texture(u_atlas, v_uv).r;
+    float width = fwidth(dist);
+    float alpha = smoothstep(EDGE - width, EDGE + width, dist);
+
+    if (alpha < 0.01) {
+        discard;
+    }
+
+#ifdef PICKING
+    f_id = uvec4(u_pick_id, v_instance, 0u, 0u);
+#else
+    vec4 color = vec4(
+        float((v_color & 0x000000FFu)) / 255.,
+        float((v_color & 0x0000FF00u) >> 8) / 255.,
+        float((v_color & 0x00FF0000u) >> 16) / 255.,
+        float((v_color & 0xFF000000u) >> 24) / 255.);
+    color.a *= alpha;
+    f_color = vec4(color.rgb * color.a, color.a);
+#endif
+}
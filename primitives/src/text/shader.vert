@@ -0,0 +1,36 @@
+#version 300 es
+
+in vec2 anchor;
+in vec4 quad;
+in vec4 uv;
+in float rotation;
+in uint color;
+in uint text;
+in vec2 rect_position;
+
+flat out uint v_color;
+#ifdef PICKING
+flat out uint v_instance;
+#endif
+out vec2 v_uv;
+
+uniform mat4 u_transform;
+uniform vec2 u_resolution;
+
+void main() {
+    // Corner of the glyph quad, in CSS pixels from the anchor point.
+    vec2 corner = mix(quad.xy, quad.zw, rect_position);
+    float c = cos(rotation);
+    float s = sin(rotation);
+    corner = vec2(c * corner.x - s * corner.y, s * corner.x + c * corner.y);
+
+    gl_Position = vec4(anchor, 0., 1.) * u_transform;
+    gl_Position.xy += corner * 2. / u_resolution * gl_Position.w;
+
+    v_uv = mix(uv.xy, uv.zw, rect_position);
+    v_color = color;
+
+#ifdef PICKING
+    v_instance = text;
+#endif
+}
@@ -0,0 +1,196 @@
+use limelight::{shadow_gpu::UniformValue, Uniform, UniformSource};
+use limelight_primitives::{
+    Circle, CircleArc, CircleLayer, DashedLine, Hairline, HairlineLayer, Line, LineLayer, Orientation, Rect, RectLayer,
+    RoundedRect,
+};
+
+/// Stands in for the renderer's global uniforms.
+struct Globals(Option<[[f32; 4]; 4]>);
+
+impl UniformSource for Globals {
+    fn uniform_value(&self, name: &str) -> Option<UniformValue> {
+        match name {
+            "u_transform" => self.0.map(UniformValue::Mat4),
+            _ => None,
+        }
+    }
+}
+
+const NO_GLOBALS: Globals = Globals(None);
+
+#[test]
+fn test_circle_hit_test_uses_inverse_transform() {
+    // Scale by 2 and translate by (0.5, 0) into clip space.
+    let layer = CircleLayer::new_transform(Uniform::new([
+        [2., 0., 0., 0.5],
+        [0., 2., 0., 0.],
+        [0., 0., 1., 0.],
+        [0., 0., 0., 1.],
+    ]));
+    layer.import(vec![
+        Circle {
+            position: [0., 0.],
+            radius: 0.1,
+            color: palette::named::WHITE.into(),
+        },
+        Circle {
+            position: [0.05, 0.],
+            radius: 0.1,
+            color: palette::named::WHITE.into(),
+        },
+    ]);
+
+    // Overlapping circles resolve to the one drawn last.
+    assert_eq!(Some(1), layer.hit_test(&NO_GLOBALS, [0.6, 0.]));
+    assert_eq!(Some(0), layer.hit_test(&NO_GLOBALS, [0.35, 0.]));
+    assert_eq!(None, layer.hit_test(&NO_GLOBALS, [0.5, 0.5]));
+
+    // The index is rebuilt when the buffer changes.
+    layer.import(vec![]);
+    assert_eq!(None, layer.hit_test(&NO_GLOBALS, [0.6, 0.]));
+}
+
+#[test]
+fn test_circle_arc_hit_test() {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    let layer = CircleLayer::arcs();
+    layer.import(vec![
+        // The upper half of a ring.
+        CircleArc {
+            position: [0., 0.],
+            radius: 1.,
+            inner_radius: 0.5,
+            start_angle: 0.,
+            end_angle: PI,
+            stroke_width: 0.,
+            color: palette::named::WHITE.into(),
+        },
+        // The outline of a quarter pie slice in the lower right.
+        CircleArc {
+            position: [0., 0.],
+            radius: 0.4,
+            inner_radius: 0.,
+            start_angle: -FRAC_PI_2,
+            end_angle: 0.,
+            stroke_width: 0.05,
+            color: palette::named::WHITE.into(),
+        },
+    ]);
+
+    assert_eq!(Some(0), layer.hit_test(&NO_GLOBALS, [0., 0.75]));
+    assert_eq!(None, layer.hit_test(&NO_GLOBALS, [0., 0.25]));
+    assert_eq!(None, layer.hit_test(&NO_GLOBALS, [0., -0.75]));
+
+    assert_eq!(Some(1), layer.hit_test(&NO_GLOBALS, [0.39, -0.01]));
+    assert_eq!(Some(1), layer.hit_test(&NO_GLOBALS, [0.2, -0.02]));
+    assert_eq!(None, layer.hit_test(&NO_GLOBALS, [0.2, -0.2]));
+}
+
+#[test]
+fn test_rect_and_line_hit_test() {
+    let rects = RectLayer::new();
+    rects.import(vec![Rect {
+        upper_left: [-0.5, 0.5],
+        lower_right: [0.5, -0.5],
+        color: palette::named::WHITE.into(),
+    }]);
+    assert_eq!(Some(0), rects.hit_test(&NO_GLOBALS, [0.25, -0.25]));
+    assert_eq!(None, rects.hit_test(&NO_GLOBALS, [0.75, 0.]));
+
+    // Rounded corners and rotation around the center.
+    let rounded = RectLayer::rounded();
+    rounded.import(vec![RoundedRect {
+        upper_left: [-0.5, 0.25],
+        lower_right: [0.5, -0.25],
+        color: palette::named::WHITE.into(),
+        corner_radius: 0.2,
+        border_width: 0.,
+        border_color: palette::named::BLACK.into(),
+        rotation: std::f32::consts::FRAC_PI_2,
+    }]);
+    assert_eq!(Some(0), rounded.hit_test(&NO_GLOBALS, [0., 0.45]));
+    assert_eq!(None, rounded.hit_test(&NO_GLOBALS, [0.45, 0.]));
+    assert_eq!(None, rounded.hit_test(&NO_GLOBALS, [0.24, 0.49]));
+    assert_eq!(Some(0), rounded.hit_test(&NO_GLOBALS, [0.2, 0.3]));
+
+    let lines = LineLayer::new();
+    lines.import(vec![Line {
+        start: [0., 0.],
+        end: [1., 1.],
+        width: 0.1,
+        color: palette::named::WHITE.into(),
+    }]);
+    assert_eq!(Some(0), lines.hit_test(&NO_GLOBALS, [0.5, 0.55]));
+    assert_eq!(None, lines.hit_test(&NO_GLOBALS, [0.5, 0.7]));
+    assert_eq!(None, lines.hit_test(&NO_GLOBALS, [1.05, 1.05]));
+
+    // Gaps between dashes count as part of the line.
+    let dashed = LineLayer::dashed();
+    dashed.import(vec![DashedLine {
+        start: [0., 0.],
+        end: [1., 1.],
+        width: 0.1,
+        color: palette::named::WHITE.into(),
+        dash_length: 8.,
+        dash_gap: 4.,
+        dash_phase: 0.,
+    }]);
+    assert_eq!(Some(0), dashed.hit_test(&NO_GLOBALS, [0.5, 0.55]));
+    assert_eq!(None, dashed.hit_test(&NO_GLOBALS, [0.5, 0.7]));
+}
+
+#[test]
+fn test_hairline_hit_test() {
+    let layer = HairlineLayer::new().with_hit_tolerance(0.02);
+    layer.import(vec![
+        Hairline {
+            location: 0.5,
+            color: palette::named::WHITE.into(),
+            orientation: Orientation::Horizontal,
+        },
+        Hairline {
+            location: -0.5,
+            color: palette::named::WHITE.into(),
+            orientation: Orientation::Vertical,
+        },
+    ]);
+
+    assert_eq!(Some(0), layer.hit_test(&NO_GLOBALS, [0.9, 0.51]));
+    assert_eq!(Some(1), layer.hit_test(&NO_GLOBALS, [-0.49, 0.5]));
+    assert_eq!(None, layer.hit_test(&NO_GLOBALS, [0., 0.]));
+}
+
+#[test]
+fn test_global_layers_hit_test_with_global_transform() {
+    // Scale by 2 and translate by (0, 0.5) into clip space.
+    let globals = Globals(Some([
+        [2., 0., 0., 0.],
+        [0., 2., 0., 0.5],
+        [0., 0., 1., 0.],
+        [0., 0., 0., 1.],
+    ]));
+
+    let circles = CircleLayer::new_global();
+    circles.import(vec![Circle {
+        position: [0.25, 0.],
+        radius: 0.1,
+        color: palette::named::WHITE.into(),
+    }]);
+    assert_eq!(Some(0), circles.hit_test(&globals, [0.5, 0.5]));
+    assert_eq!(None, circles.hit_test(&globals, [0.25, 0.]));
+
+    // Layers with their own transform ignore the global one.
+    let own = CircleLayer::new();
+    own.import(circles.export());
+    assert_eq!(Some(0), own.hit_test(&globals, [0.25, 0.]));
+
+    let hairlines = HairlineLayer::new_global().with_hit_tolerance(0.02);
+    hairlines.import(vec![Hairline {
+        location: 0.25,
+        color: palette::named::WHITE.into(),
+        orientation: Orientation::Horizontal,
+    }]);
+    assert_eq!(Some(0), hairlines.hit_test(&globals, [0., 1.]));
+    assert_eq!(None, hairlines.hit_test(&globals, [0., 0.25]));
+}
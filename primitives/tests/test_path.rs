@@ -0,0 +1,143 @@
+use limelight_primitives::{Path, PathCommand, Subpath};
+
+fn path(commands: Vec<PathCommand>) -> Path {
+    Path {
+        commands,
+        fill: None,
+        stroke: None,
+    }
+}
+
+/// An arc of radius 1 from `[1, 0]` to `[0, 1]`.
+fn unit_arc(large_arc: bool, sweep: bool) -> Path {
+    path(vec![
+        PathCommand::MoveTo([1., 0.]),
+        PathCommand::ArcTo {
+            radii: [1., 1.],
+            rotation: 0.,
+            large_arc,
+            sweep,
+            to: [0., 1.],
+        },
+    ])
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - b[0]).hypot(a[1] - b[1])
+}
+
+#[test]
+fn test_quarter_arc_stays_within_tolerance() {
+    let tolerance = 0.01;
+    let subpaths = unit_arc(false, true).flatten(tolerance);
+    assert_eq!(1, subpaths.len());
+
+    let points = &subpaths[0].points;
+    assert!(points.len() > 2);
+    assert_eq!([1., 0.], points[0]);
+    assert_eq!([0., 1.], *points.last().unwrap());
+
+    for point in points {
+        assert!((distance(*point, [0., 0.]) - 1.).abs() < 1e-5, "{:?} is off the circle", point);
+        assert!(point[0] >= -1e-5 && point[1] >= -1e-5, "{:?} is outside the quarter", point);
+    }
+
+    // The middle of each chord is where it strays furthest from the arc.
+    for pair in points.windows(2) {
+        let middle = [(pair[0][0] + pair[1][0]) / 2., (pair[0][1] + pair[1][1]) / 2.];
+        assert!(1. - distance(middle, [0., 0.]) <= tolerance);
+    }
+}
+
+#[test]
+fn test_arc_flags_pick_the_center() {
+    // The small arcs turn through a quarter circle, the large ones through
+    // three quarters, around whichever center puts them on the side that
+    // `sweep` asks for.
+    for (large_arc, sweep, center) in [
+        (false, true, [0., 0.]),
+        (false, false, [1., 1.]),
+        (true, false, [0., 0.]),
+        (true, true, [1., 1.]),
+    ] {
+        let points = &unit_arc(large_arc, sweep).flatten(0.01)[0].points;
+
+        for point in points {
+            assert!(
+                (distance(*point, center) - 1.).abs() < 1e-5,
+                "large_arc: {}, sweep: {}: {:?} is not on the circle around {:?}",
+                large_arc,
+                sweep,
+                point,
+                center,
+            );
+        }
+
+        // Positive sweeps turn counterclockwise around the center (with y
+        // up), like SVG's clockwise with y down.
+        let [a, b] = [points[0], points[1]];
+        let cross = (a[0] - center[0]) * (b[1] - center[1]) - (a[1] - center[1]) * (b[0] - center[0]);
+        assert_eq!(sweep, cross > 0.);
+    }
+
+    let small = unit_arc(false, true).flatten(0.01)[0].points.len();
+    let large = unit_arc(true, false).flatten(0.01)[0].points.len();
+    assert!(large > small);
+}
+
+#[test]
+fn test_close_marks_subpath_closed() {
+    let subpaths = path(vec![
+        PathCommand::MoveTo([0., 0.]),
+        PathCommand::LineTo([1., 0.]),
+        PathCommand::LineTo([1., 1.]),
+        PathCommand::Close,
+        PathCommand::MoveTo([2., 2.]),
+        PathCommand::LineTo([3., 2.]),
+    ])
+    .flatten(0.01);
+
+    assert_eq!(
+        vec![
+            Subpath {
+                points: vec![[0., 0.], [1., 0.], [1., 1.]],
+                closed: true,
+            },
+            Subpath {
+                points: vec![[2., 2.], [3., 2.]],
+                closed: false,
+            },
+        ],
+        subpaths
+    );
+}
+
+#[test]
+fn test_segments_increase_as_tolerance_shrinks() {
+    let curves = [
+        unit_arc(false, true),
+        path(vec![
+            PathCommand::MoveTo([0., 0.]),
+            PathCommand::QuadTo {
+                control: [1., 2.],
+                to: [2., 0.],
+            },
+        ]),
+        path(vec![
+            PathCommand::MoveTo([0., 0.]),
+            PathCommand::CubicTo {
+                control1: [0., 2.],
+                control2: [2., -2.],
+                to: [2., 0.],
+            },
+        ]),
+    ];
+
+    for curve in &curves {
+        let counts: Vec<usize> = [0.1, 0.01, 0.001]
+            .iter()
+            .map(|&tolerance| curve.flatten(tolerance)[0].points.len())
+            .collect();
+        assert!(counts[0] < counts[1] && counts[1] < counts[2], "{:?}", counts);
+    }
+}
@@ -0,0 +1,88 @@
+use limelight_primitives::{Polygon, PolygonLayer};
+use std::rc::Rc;
+
+fn square(min: f32, max: f32) -> Vec<[f32; 2]> {
+    vec![[min, min], [max, min], [max, max], [min, max]]
+}
+
+/// The total area of the triangles in `indices`.
+fn area(points: &[[f32; 2]], indices: &[u32]) -> f32 {
+    indices
+        .chunks(3)
+        .map(|t| {
+            let [a, b, c] = [0, 1, 2].map(|i| points[t[i] as usize]);
+            ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])).abs() / 2.
+        })
+        .sum()
+}
+
+#[test]
+fn test_polygon_with_hole_is_triangulated() {
+    let layer = PolygonLayer::new();
+    let rings = vec![square(0., 4.), square(1., 3.)];
+    layer.import(vec![Polygon {
+        rings: rings.clone(),
+        color: palette::named::WHITE.into(),
+    }]);
+
+    let triangles = layer.triangles(0).unwrap();
+    let points: Vec<[f32; 2]> = rings.concat();
+    assert_eq!(8 * 3, triangles.len());
+    assert_eq!(12., area(&points, &triangles));
+}
+
+#[test]
+fn test_only_changed_polygons_are_retriangulated() {
+    let layer = PolygonLayer::new();
+    let polygons = vec![
+        Polygon {
+            rings: vec![square(0., 1.)],
+            color: palette::named::WHITE.into(),
+        },
+        Polygon {
+            rings: vec![square(2., 3.)],
+            color: palette::named::WHITE.into(),
+        },
+    ];
+    layer.import(polygons.clone());
+    let before = [layer.triangles(0).unwrap(), layer.triangles(1).unwrap()];
+
+    let mut changed = polygons;
+    changed[0].color = palette::named::RED.into();
+    changed[1].rings = vec![square(2., 4.)];
+    layer.import(changed);
+
+    assert!(Rc::ptr_eq(&before[0], &layer.triangles(0).unwrap()));
+    assert!(!Rc::ptr_eq(&before[1], &layer.triangles(1).unwrap()));
+}
+
+#[test]
+fn test_inserting_a_polygon_keeps_later_triangulations() {
+    let layer = PolygonLayer::new();
+    let mut polygons = vec![
+        Polygon {
+            rings: vec![square(0., 1.)],
+            color: palette::named::WHITE.into(),
+        },
+        Polygon {
+            rings: vec![square(2., 3.)],
+            color: palette::named::WHITE.into(),
+        },
+    ];
+    layer.import(polygons.clone());
+    let before = [layer.triangles(0).unwrap(), layer.triangles(1).unwrap()];
+
+    polygons.insert(
+        0,
+        Polygon {
+            rings: vec![square(4., 6.)],
+            color: palette::named::RED.into(),
+        },
+    );
+    layer.import(polygons);
+
+    assert_eq!(6, layer.triangles(0).unwrap().len());
+    assert!(Rc::ptr_eq(&before[0], &layer.triangles(1).unwrap()));
+    assert!(Rc::ptr_eq(&before[1], &layer.triangles(2).unwrap()));
+    assert!(layer.triangles(3).is_none());
+}
@@ -0,0 +1,34 @@
+#![cfg(feature = "serde")]
+
+use limelight_primitives::{Circle, Color};
+
+#[test]
+fn test_color_hex_round_trip() {
+    let color: Color = palette::named::ORANGERED.into();
+    let json = serde_json::to_string(&color.opacity(0.5)).unwrap();
+    assert_eq!("\"#ff45007f\"", json);
+
+    let parsed: Color = serde_json::from_str(&json).unwrap();
+    assert_eq!(color.opacity(0.5).0, parsed.0);
+
+    let opaque: Color = serde_json::from_str("\"#FF4500\"").unwrap();
+    assert_eq!(color.0, opaque.0);
+
+    assert!(serde_json::from_str::<Color>("\"#ff45\"").is_err());
+    assert!(serde_json::from_str::<Color>("\"#+f+f+f\"").is_err());
+}
+
+#[test]
+fn test_circle_round_trip() {
+    let circles = vec![Circle {
+        position: [0.5, -0.25],
+        radius: 0.1,
+        color: palette::named::WHITE.into(),
+    }];
+
+    let json = serde_json::to_string(&circles).unwrap();
+    let parsed: Vec<Circle> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(circles[0].position, parsed[0].position);
+    assert_eq!(circles[0].color.0, parsed[0].color.0);
+}
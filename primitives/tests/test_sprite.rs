@@ -0,0 +1,21 @@
+use limelight_primitives::SpriteAtlas;
+
+#[test]
+fn test_sprite_atlas_packs_images() {
+    let mut atlas = SpriteAtlas::new(16, 16);
+
+    let first = atlas.add(4, 2, &[255; 4 * 2 * 4]).unwrap();
+    assert_eq!([1. / 16., 1. / 16., 5. / 16., 3. / 16.], first);
+
+    // Images are padded by a pixel on each side.
+    let second = atlas.add(8, 8, &[255; 8 * 8 * 4]).unwrap();
+    assert_eq!([7. / 16., 1. / 16., 15. / 16., 9. / 16.], second);
+
+    assert!(atlas.add(2, 2, &[255; 3]).is_err());
+    assert!(atlas.add(16, 1, &[255; 16 * 4]).is_err());
+    assert!(atlas.add(0, 2, &[]).is_err());
+    assert!(atlas.add(2, 0, &[]).is_err());
+
+    let texture = atlas.texture().unwrap();
+    assert_eq!(16, texture.width());
+}
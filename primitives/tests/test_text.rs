@@ -0,0 +1,45 @@
+use limelight::Result;
+use limelight_primitives::{FontMetrics, GlyphRasterizer, RasterizedGlyph, TextLayer};
+
+/// Rasterizes every glyph as a solid box, half as wide as the font size.
+struct BoxRasterizer;
+
+impl GlyphRasterizer for BoxRasterizer {
+    fn rasterize(&mut self, ch: char, font_size: f32) -> Result<RasterizedGlyph> {
+        let advance = font_size / 2.;
+        let (width, height) = if ch == ' ' {
+            (0, 0)
+        } else {
+            (advance as u32, (font_size * 0.75) as u32)
+        };
+
+        Ok(RasterizedGlyph {
+            width,
+            height,
+            left: 0.,
+            top: height as f32,
+            advance,
+            alpha: vec![255; (width * height) as usize],
+        })
+    }
+
+    fn font_metrics(&mut self, font_size: f32) -> Result<FontMetrics> {
+        Ok(FontMetrics {
+            ascent: font_size * 0.8,
+            descent: font_size * 0.2,
+        })
+    }
+}
+
+#[test]
+fn test_measure_scales_with_font_size() {
+    let mut layer = TextLayer::new().with_font(BoxRasterizer);
+
+    let metrics = layer.measure("ab c", 16.).unwrap();
+    assert_eq!(32., metrics.width);
+    assert_eq!(12.8, metrics.ascent);
+    assert_eq!(3.2, metrics.descent);
+
+    let metrics = layer.measure("ab c", 64.).unwrap();
+    assert_eq!(128., metrics.width);
+}
@@ -232,12 +232,10 @@ impl<Controller: LimelightController> Component for LimelightComponent<Controlle
     fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
         let props = ctx.props();
         let link = ctx.link();
-        let device_pixel_ratio = window().unwrap().device_pixel_ratio();
 
+        // The drawing buffer size is set by `Renderer::resize` in `rendered`.
         html! {
             <canvas
-                height={(props.height as f64 * device_pixel_ratio).to_string()}
-                width={(props.width as f64 * device_pixel_ratio).to_string()}
                 style={format!("width: {}px; height: {}px;", props.width, props.height)}
                 onmousedown={link.callback(Msg::MouseDown)}
                 onmousemove={link.callback(Msg::MouseMove)}
@@ -279,6 +277,14 @@ impl<Controller: LimelightController> Component for LimelightComponent<Controlle
             }
 
             self.renderer = Some(Renderer::new(gl));
+        }
+
+        if let Some(renderer) = &mut self.renderer {
+            let props = ctx.props();
+            let device_pixel_ratio = window().unwrap().device_pixel_ratio();
+            renderer
+                .resize(props.width as _, props.height as _, device_pixel_ratio)
+                .unwrap();
 
             self.request_render(ctx);
         }